@@ -0,0 +1,28 @@
+use jsco::feature::{es_year_label, JsFeature, JsFeatureTrait};
+
+/// Describes a feature for `--explain`: its BCD description, docs link,
+/// ES edition and the first supporting version per browser.
+pub fn explain(feature: JsFeature) -> String {
+  let compat = feature.compat();
+  let mut lines = vec![format!("{:?} ({})", feature, feature.key())];
+  if let Some(description) = compat.description() {
+    lines.push(format!("  {}", description));
+  }
+  if let Some(year) = feature.es_year() {
+    lines.push(format!("  Standard: {}", es_year_label(year)));
+  }
+  lines.push(format!("  Docs: {}", feature.mdn_url()));
+
+  let mut support = feature.browser_support().into_iter().collect::<Vec<_>>();
+  support.sort();
+  lines.push("  Support:".to_string());
+  for (browser, version) in support {
+    let version = match version.as_str() {
+      "true" => "yes".to_string(),
+      "false" => "no".to_string(),
+      _ => version,
+    };
+    lines.push(format!("    {:<8} {}", browser, version));
+  }
+  lines.join("\n")
+}
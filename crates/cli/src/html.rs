@@ -0,0 +1,484 @@
+use jsco::feature::{es_year_label, BrowserSupport, Location, Severity};
+use jsco::report::{group_by_feature, min_required_versions, Reports};
+use maud::{html, Markup};
+use std::collections::BTreeMap;
+
+use crate::is_supported;
+use crate::targets::{Target, TargetSource};
+
+/// Renders the HTML report for `reports` evaluated against `targets`, which
+/// came from `target_source`, without touching the filesystem. `concise`
+/// leaves out the target browsers panel and decorative icons, for embedding
+/// in a larger page; `by_feature` lists each feature with the files using it
+/// instead of the other way round.
+pub fn render_html(
+  reports: &Reports,
+  targets: &[Target],
+  target_source: &TargetSource,
+  concise: bool,
+  by_feature: bool,
+) -> String {
+  let mut chrome_versions = Vec::new();
+  let mut firefox_versions = Vec::new();
+  let mut safari_versions = Vec::new();
+  let mut edge_versions = Vec::new();
+  let mut other_browsers = Vec::new();
+  for browser in targets {
+    let name = browser.name().to_lowercase();
+    match name.as_str() {
+      "chrome" | "and_chr" | "chrome android" => {
+        chrome_versions.push(browser.version().to_string())
+      }
+      "firefox" | "firefox android" => firefox_versions.push(browser.version().to_string()),
+      "safari" | "ios_saf" => safari_versions.push(browser.version().to_string()),
+      "edge" => edge_versions.push(browser.version().to_string()),
+      _ => other_browsers.push((browser.name(), browser.version())),
+    }
+  }
+
+  // Group and sort versions
+  let format_versions = |versions: &[String]| -> String {
+    let mut versions = versions.to_vec();
+    versions.sort();
+    versions.join(", ")
+  };
+
+  let default_version = "0".to_string();
+  chrome_versions.sort();
+  firefox_versions.sort();
+  safari_versions.sort();
+  edge_versions.sort();
+
+  let year_counts = es_year_counts(reports);
+  let max_year_count = year_counts.values().copied().max().unwrap_or(0).max(1);
+
+  let markup = html! {
+    html class="bg-slate-50" {
+      head {
+        title { "JavaScript Compatibility Report" }
+        script src="https://cdn.tailwindcss.com" {}
+        meta charset="UTF-8" {}
+        meta name="viewport" content="width=device-width, initial-scale=1.0" {}
+        style type="text/css" {
+          (r#"
+          summary {
+            list-style: none;
+          }
+          summary::-webkit-details-marker {
+            display: none;
+          }
+          summary::marker {
+            display: none;
+          }
+          .code-block {
+            background-image: linear-gradient(to bottom, #f8fafc, #f1f5f9);
+          }
+          details[open] summary ~ * {
+            animation: sweep .3s ease-in-out;
+          }
+          @keyframes sweep {
+            0%    {opacity: 0; transform: translateY(-10px)}
+            100%  {opacity: 1; transform: translateY(0)}
+          }
+          .feature-card {
+            transition: all 0.2s ease-in-out;
+          }
+          .feature-card:hover {
+            transform: translateY(-2px);
+            box-shadow: 0 8px 24px -12px rgba(0, 0, 0, 0.15);
+          }
+          "#)
+        }
+      }
+      body class="min-h-screen p-4 md:p-8 bg-gradient-to-br from-slate-50 to-slate-100/50" {
+        div class="max-w-6xl mx-auto" {
+          div class="text-center mb-16" {
+            h1 class="text-4xl font-bold text-slate-900 mb-4 bg-clip-text text-transparent bg-gradient-to-r from-blue-600 to-blue-800" {
+              "JavaScript Compatibility Report"
+            }
+            p class="text-lg text-slate-600 max-w-2xl mx-auto" {
+              "Compatibility analysis based on MDN browser-compat-data"
+            }
+            @if let Some(summary) = requirement_summary(&min_required_versions(reports)) {
+              div class="mt-4 inline-block px-4 py-1.5 rounded-full text-sm font-medium bg-blue-50 text-blue-700 border border-blue-100" {
+                (summary)
+              }
+            }
+            @if !concise && !year_counts.is_empty() {
+              div class="mt-8 max-w-md mx-auto" {
+                svg xmlns="http://www.w3.org/2000/svg" width="100%" viewBox=(format!("0 0 320 {}", year_counts.len() * 20)) {
+                  @for (row, (year, count)) in year_counts.iter().enumerate() {
+                    @let y = row * 20;
+                    @let width = (count * 200 / max_year_count).max(1);
+                    g class="es-year" data-year=(year) {
+                      text x="0" y=(y + 14) font-size="12" fill="#475569" { (es_year_label(*year)) }
+                      rect x="70" y=(y + 3) width=(width) height="14" rx="3" fill="#3b82f6" {}
+                      text x=(width + 76) y=(y + 14) font-size="12" fill="#475569" { (count) }
+                    }
+                  }
+                }
+              }
+            }
+          }
+
+          @if !concise {
+            div class="mb-12 bg-white rounded-xl shadow-sm border border-slate-200/60 p-6" {
+              h2 class="text-lg font-semibold text-slate-800 mb-4" {
+                "Target Browsers"
+                span class="ml-2 text-sm font-normal text-slate-500" {
+                  "(" (target_source) ")"
+                }
+              }
+              div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4" {
+                @if !chrome_versions.is_empty() {
+                  div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
+                    div class="flex items-center gap-2 text-slate-700 mb-3" {
+                      span class="font-medium" { "Chrome" }
+                    }
+                    div class="space-y-1.5" {
+                      div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
+                      div class="text-sm text-slate-700 font-mono" {
+                        @let min_version = chrome_versions.iter().min().unwrap_or(&default_version);
+                        @let max_version = chrome_versions.iter().max().unwrap_or(&default_version);
+                        @if min_version == max_version {
+                          (format!("v{}", min_version))
+                        } @else {
+                          (format!("v{} - v{}", min_version, max_version))
+                        }
+                      }
+                      div class="text-xs text-slate-500 mt-1" {
+                        (format!("Including: {}", format_versions(&chrome_versions)))
+                      }
+                    }
+                  }
+                }
+
+                @if !firefox_versions.is_empty() {
+                  div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
+                    div class="flex items-center gap-2 text-slate-700 mb-3" {
+                      span class="font-medium" { "Firefox" }
+                    }
+                    div class="space-y-1.5" {
+                      div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
+                      div class="text-sm text-slate-700 font-mono" {
+                        @let min_version = firefox_versions.iter().min().unwrap_or(&default_version);
+                        @let max_version = firefox_versions.iter().max().unwrap_or(&default_version);
+                        @if min_version == max_version {
+                          (format!("v{}", min_version))
+                        } @else {
+                          (format!("v{} - v{}", min_version, max_version))
+                        }
+                      }
+                      div class="text-xs text-slate-500 mt-1" {
+                        (format!("Including: {}", format_versions(&firefox_versions)))
+                      }
+                    }
+                  }
+                }
+
+                @if !safari_versions.is_empty() {
+                  div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
+                    div class="flex items-center gap-2 text-slate-700 mb-3" {
+                      span class="font-medium" { "Safari" }
+                    }
+                    div class="space-y-1.5" {
+                      div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
+                      div class="text-sm text-slate-700 font-mono" {
+                        @let min_version = safari_versions.iter().min().unwrap_or(&default_version);
+                        @let max_version = safari_versions.iter().max().unwrap_or(&default_version);
+                        @if min_version == max_version {
+                          (format!("v{}", min_version))
+                        } @else {
+                          (format!("v{} - v{}", min_version, max_version))
+                        }
+                      }
+                      div class="text-xs text-slate-500 mt-1" {
+                        (format!("Including: {}", format_versions(&safari_versions)))
+                      }
+                    }
+                  }
+                }
+
+                @if !edge_versions.is_empty() {
+                  div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
+                    div class="flex items-center gap-2 text-slate-700 mb-3" {
+                      span class="font-medium" { "Edge" }
+                    }
+                    div class="space-y-1.5" {
+                      div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
+                      div class="text-sm text-slate-700 font-mono" {
+                        @let min_version = edge_versions.iter().min().unwrap_or(&default_version);
+                        @let max_version = edge_versions.iter().max().unwrap_or(&default_version);
+                        @if min_version == max_version {
+                          (format!("v{}", min_version))
+                        } @else {
+                          (format!("v{} - v{}", min_version, max_version))
+                        }
+                      }
+                      div class="text-xs text-slate-500 mt-1" {
+                        (format!("Including: {}", format_versions(&edge_versions)))
+                      }
+                    }
+                  }
+                }
+
+                @if !other_browsers.is_empty() {
+                  div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
+                    div class="flex items-center gap-2 text-slate-700 mb-3" {
+                      span class="font-medium" { "Other" }
+                    }
+                    div class="space-y-2" {
+                      @for (name, version) in &other_browsers {
+                        div class="text-sm text-slate-600" {
+                          span class="font-medium" { (name) }
+                          span class="font-mono ml-2" { (format!("v{}", version)) }
+                        }
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+
+          @let unparsed = reports.iter().filter(|report| report.has_parse_errors()).collect::<Vec<_>>();
+          @if !unparsed.is_empty() {
+            div class="mb-12 rounded-xl border border-amber-200 bg-amber-50 p-6" {
+              h3 class="text-lg font-medium text-amber-800 mb-4" { "Files that couldn't be parsed" }
+              @for report in &unparsed {
+                div class="mb-4 last:mb-0" {
+                  div class="font-mono text-sm text-amber-900 mb-1" { (report.path) }
+                  ul class="list-disc ml-6 text-sm text-amber-800" {
+                    @for error in report.parse_errors.lock().unwrap().iter() {
+                      li { (error) }
+                    }
+                  }
+                }
+              }
+            }
+          }
+
+          @if by_feature {
+            @for group in group_by_feature(reports) {
+              div class="feature-card bg-white rounded-xl shadow-sm border border-slate-200/60 p-6 mb-8 transition-all" {
+                div class="flex flex-col md:flex-row md:items-start md:justify-between mb-6" {
+                  div {
+                    h2 class="text-xl font-semibold text-blue-600/90 mb-3" {
+                      (format!("{:?}", group.feature))
+                      @if group.deprecated {
+                        span class="ml-2 align-middle px-2 py-0.5 rounded-full text-xs font-medium bg-amber-50 text-amber-700 border border-amber-100" {
+                          "Deprecated"
+                        }
+                      }
+                    }
+                    a class="text-sm text-slate-500 hover:text-blue-500 hover:underline" href=(group.mdn_url) target="_blank" rel="noopener" {
+                      "MDN Documentation"
+                    }
+                  }
+                  (support_badges(&group.support, targets))
+                }
+                @for usage in &group.files {
+                  details class="group mt-4" {
+                    summary class="font-mono text-sm text-slate-700 cursor-pointer hover:text-blue-600 transition-colors" {
+                      (format!("{} ({} locations)", usage.path, usage.locations.len()))
+                    }
+                    div class="space-y-4 mt-4" {
+                      @for location in usage.locations {
+                        (location_block(location))
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          } @else {
+            @for report in reports {
+              @if !report.found_features.is_empty() {
+                div class="space-y-8 mb-12" {
+                  h3 class="text-lg font-medium text-slate-700 mb-6 pb-2 border-b border-slate-200" {
+                    span class="inline-flex items-center gap-2" {
+                      @if !concise {
+                        svg xmlns="http://www.w3.org/2000/svg" class="w-5 h-5 text-slate-400" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
+                          path d="M13 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V9z" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+                          path d="M13 2v7h7" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+                        }
+                      }
+                      (report.path)
+                      @if let Some(summary) = requirement_summary(&report.required_versions) {
+                        span class="px-2 py-0.5 rounded-full text-xs font-medium bg-blue-50 text-blue-700 border border-blue-100" {
+                          (summary)
+                        }
+                      }
+                    }
+                  }
+                  @for feature in &report.found_features {
+                    div class="feature-card bg-white rounded-xl shadow-sm border border-slate-200/60 p-6 transition-all" {
+                      div class="flex flex-col md:flex-row md:items-start md:justify-between mb-6" {
+                        div {
+                          h2 class="text-xl font-semibold text-blue-600/90 mb-3" {
+                            (format!("{:?}", feature.feat_type))
+                            @if let Some(severity) = feature.severity {
+                              span class=(format!("ml-2 align-middle px-2 py-0.5 rounded-full text-xs font-medium border {}", severity_colors(severity))) {
+                                (format!("{:?}", severity))
+                              }
+                            }
+                            @if feature.deprecated {
+                              span class="ml-2 align-middle px-2 py-0.5 rounded-full text-xs font-medium bg-amber-50 text-amber-700 border border-amber-100" {
+                                "Deprecated"
+                              }
+                            }
+                            @if feature.experimental {
+                              span class="ml-2 align-middle px-2 py-0.5 rounded-full text-xs font-medium bg-violet-50 text-violet-700 border border-violet-100" {
+                                "Experimental"
+                              }
+                            }
+                          }
+                          a class="text-sm text-slate-500 hover:text-blue-500 hover:underline inline-flex items-center gap-1.5 group"
+                            href=(feature.mdn_url) target="_blank" rel="noopener" {
+                            @if !concise {
+                              svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4 transition-transform group-hover:translate-x-0.5" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
+                                path d="M10 6H6a2 2 0 00-2 2v10a2 2 0 002 2h10a2 2 0 002-2v-4M14 4h6m0 0v6m0-6L10 14" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+                              }
+                            }
+                            span { "MDN Documentation" }
+                          }
+                        }
+                        (support_badges(&feature.support.lock().unwrap(), targets))
+                      }
+
+                      div class="mt-6" {
+                        details class="group" {
+                          summary class="text-lg font-medium text-slate-800 cursor-pointer hover:text-blue-600 transition-colors" {
+                            span class="inline-flex items-center gap-2" {
+                              svg xmlns="http://www.w3.org/2000/svg"
+                                class="w-5 h-5 text-slate-400 group-open:rotate-90 transition-transform"
+                                viewBox="0 0 24 24" fill="none" stroke="currentColor" {
+                                path d="M9 5l7 7-7 7" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+                              }
+                              (format!("Found in {} locations", feature.locations.len()))
+                            }
+                          }
+                          div class="space-y-4 mt-4" {
+                            @for location in &feature.locations {
+                              (location_block(location))
+                            }
+                          }
+                        }
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  };
+
+  markup.into_string()
+}
+
+/// One badge per browser with the version that added the feature, green when
+/// every target of that browser supports it.
+fn support_badges(support: &BrowserSupport, targets: &[Target]) -> Markup {
+  let mut browser_info: Vec<_> = support.iter().collect();
+  browser_info.sort_by(|a, b| a.0.cmp(b.0));
+  html! {
+    div class="flex flex-wrap gap-2 mt-4 md:mt-0" {
+      @for (browser, version) in &browser_info {
+        @let is_compatible = is_supported(browser, version, targets);
+        div class=(if is_compatible {
+          "inline-flex items-center px-3 py-1.5 rounded-full text-sm bg-green-50 text-green-700 border border-green-100 shadow-sm hover:bg-green-100 transition-colors"
+        } else {
+          "inline-flex items-center px-3 py-1.5 rounded-full text-sm bg-red-50 text-red-700 border border-red-100 shadow-sm hover:bg-red-100 transition-colors"
+        }) {
+          span class="mr-1.5" {
+            @if is_compatible {
+              svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
+                path d="M20 6L9 17l-5-5" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+              }
+            } @else {
+              svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
+                path d="M18 6L6 18M6 6l12 12" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+              }
+            }
+          }
+          (format!("{} ≥ {}", browser, version))
+        }
+      }
+    }
+  }
+}
+
+/// A location's line range and source snippet.
+fn location_block(location: &Location) -> Markup {
+  html! {
+    div class="rounded-lg border border-slate-200 overflow-hidden transition-all hover:border-slate-300" {
+      div class="flex items-center justify-between px-4 py-2.5 bg-slate-50 text-sm text-slate-600 border-b border-slate-200" {
+        span class="font-medium" {
+          @if location.start_line == location.end_line {
+            (format!("Line {}:{}", location.start_line, location.start_col))
+          } @else {
+            (format!("Lines {}-{}", location.start_line, location.end_line))
+          }
+        }
+      }
+      div class="code-block p-4 font-mono text-sm overflow-x-auto" {
+        code {(location.code)}
+      }
+    }
+  }
+}
+
+/// Badge colors for a severity, red for errors down to grey for info.
+fn severity_colors(severity: Severity) -> &'static str {
+  match severity {
+    Severity::Error => "bg-red-50 text-red-700 border-red-100",
+    Severity::Warn => "bg-amber-50 text-amber-700 border-amber-100",
+    Severity::Info | Severity::Off => "bg-slate-50 text-slate-600 border-slate-200",
+  }
+}
+
+/// "Requires at least Chrome 80 / Safari 13.1" for the browsers that need a
+/// specific version, or `None` when every version will do.
+fn requirement_summary(required: &BrowserSupport) -> Option<String> {
+  let mut browsers = required
+    .iter()
+    .filter(|(_, version)| version.as_str() != "true")
+    .collect::<Vec<_>>();
+  if browsers.is_empty() {
+    return None;
+  }
+  browsers.sort();
+  let parts = browsers
+    .iter()
+    .map(|(browser, version)| {
+      let name = match browser.as_str() {
+        "chrome" => "Chrome",
+        "firefox" => "Firefox",
+        "safari" => "Safari",
+        "edge" => "Edge",
+        other => other,
+      };
+      if version.as_str() == "false" {
+        format!("{} (unsupported)", name)
+      } else {
+        format!("{} {}", name, version)
+      }
+    })
+    .collect::<Vec<String>>();
+  Some(format!("Requires at least {}", parts.join(" / ")))
+}
+
+/// Number of findings per ES year, for the header chart. Features that aren't
+/// part of an ES edition are left out.
+fn es_year_counts(reports: &Reports) -> BTreeMap<u16, usize> {
+  let mut counts = BTreeMap::new();
+  for feature in reports.iter().flat_map(|report| &report.found_features) {
+    if let Some(year) = feature.feat_type.es_year() {
+      *counts.entry(year).or_default() += feature.locations.len();
+    }
+  }
+  counts
+}
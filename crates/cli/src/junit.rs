@@ -0,0 +1,67 @@
+use jsco::report::Reports;
+
+use crate::severity::is_feature_supported;
+use crate::targets::Target;
+
+/// Escapes text for XML attributes and content.
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+/// Renders JUnit XML with one test case per file and feature. Features the
+/// targets don't support fail, listing the browsers' first supporting
+/// versions; informational features always pass.
+pub fn render_junit(reports: &Reports, targets: &[Target]) -> String {
+  let mut cases = Vec::new();
+  let mut failures = 0;
+  for report in reports {
+    let mut features = report.found_features.iter().collect::<Vec<_>>();
+    features.sort_by_key(|feature| feature.feat_type.key());
+    for feature in features {
+      let name = escape(feature.feat_type.key());
+      let classname = escape(&report.path);
+      if feature.feat_type.is_informational() || is_feature_supported(feature, targets) {
+        cases.push(format!(
+          "    <testcase classname=\"{}\" name=\"{}\"/>",
+          classname, name
+        ));
+        continue;
+      }
+
+      failures += 1;
+      let mut support = feature
+        .support
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(browser, version)| format!("{} {}", browser, version))
+        .collect::<Vec<String>>();
+      support.sort();
+      let lines = feature
+        .locations
+        .iter()
+        .map(|location| location.start_line.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+      cases.push(format!(
+        "    <testcase classname=\"{}\" name=\"{}\">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+        classname,
+        name,
+        escape(&format!("Not supported by the target browsers (requires {})", support.join(", "))),
+        escape(&format!("Used on lines {}", lines))
+      ));
+    }
+  }
+
+  format!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n  <testsuite name=\"jsco\" tests=\"{}\" failures=\"{}\">\n{}\n  </testsuite>\n</testsuites>\n",
+    cases.len(),
+    failures,
+    cases.join("\n")
+  )
+}
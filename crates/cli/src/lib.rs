@@ -1,413 +1,803 @@
-use browserslist::{execute, Distrib, Opts};
-use clap::Parser;
-use jsco::jsco;
-use jsco::report::Reports;
-use maud::html;
-use oxc::allocator::Allocator;
+use chrono::{DateTime, FixedOffset, Local};
+use clap::{Parser, Subcommand, ValueEnum};
+use jsco::feature::{BrowserSupport, JsFeature, Severity};
+use jsco::profile::Profile;
+use jsco::report::{group_by_feature, min_required_versions, FeatureGroup, Report, Reports};
+use jsco::version::{compare_versions, parse_version};
+use jsco::{jsco_with_options, CustomMethod, Options, SourceKind};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self};
 use std::io::Write;
 use std::sync::OnceLock;
-use std::{
-  fs::{self},
-  path::PathBuf,
-  sync::Arc,
-};
-
-const CACHE_DIR: &str = ".jsco-cache";
+use std::time::{Duration, Instant};
+
+pub mod explain;
+pub mod html;
+pub mod junit;
+pub mod markdown;
+pub mod merge;
+pub mod sarif;
+pub mod severity;
+pub mod targets;
+pub mod text;
+
+use explain::explain;
+pub use html::render_html;
+pub use junit::render_junit;
+pub use markdown::render_markdown;
+use merge::{merge_documents, parse_document, summary};
+pub use sarif::render_sarif;
+use severity::{is_feature_supported, parse_severity_rule, severity_for, SeverityMap};
+use targets::{load_targets_file, resolve_query, resolve_targets, Preset, Target, TargetSource};
+pub use text::render_text;
 
 static CLIENT: OnceLock<Client> = OnceLock::new();
-static ALLOCATOR: OnceLock<Arc<Allocator>> = OnceLock::new();
 
 #[derive(Parser, Debug)]
-#[command(version, about = "JavaScript Compatibility Checker")]
+#[command(
+  version,
+  about = "JavaScript Compatibility Checker",
+  subcommand_negates_reqs = true
+)]
 struct Args {
+  #[command(subcommand)]
+  command: Option<Command>,
+
   /// JavaScript files, URLs, or glob patterns to check
-  #[arg(required = true)]
+  #[arg(required_unless_present = "explain")]
   inputs: Vec<String>,
 
-  /// Output format: console or json
-  #[arg(short, long, default_value = "console")]
-  format: String,
+  /// Output formats, comma-separated or repeated: console, html, json,
+  /// markdown, sarif, junit or keys
+  #[arg(short, long, value_delimiter = ',', default_value = "console")]
+  format: Vec<String>,
+
+  /// Suppress progress and summary output
+  #[arg(short, long)]
+  quiet: bool,
+
+  /// Enable an opt-in detector by name or BCD key (repeatable)
+  #[arg(long = "enable", value_name = "FEATURE", value_parser = parse_feature)]
+  enable: Vec<JsFeature>,
+
+  /// Report the ES year each method chain requires
+  #[arg(long)]
+  chain_analysis: bool,
+
+  /// Print per-stage timing to stderr
+  #[arg(long)]
+  profile: bool,
+
+  /// Refresh cached BCD data older than this many days; with --offline,
+  /// such data is an error
+  #[arg(long, value_name = "DAYS", value_parser = parse_bcd_age)]
+  max_bcd_age: Option<Duration>,
+
+  /// Override a feature's severity: off, info, warn or error (repeatable).
+  /// Any feature mapped to error makes the run exit non-zero
+  #[arg(long = "severity", value_name = "FEATURE=LEVEL", value_parser = parse_severity_rule)]
+  severity: Vec<(JsFeature, Severity)>,
+
+  /// Exit non-zero when any detected feature isn't supported by the targets
+  #[arg(long)]
+  fail_on_incompatible: bool,
+
+  /// List which target browsers block each detected feature
+  #[arg(long)]
+  list_unsupported: bool,
+
+  /// Use a predefined target set instead of .browserslistrc
+  #[arg(long, value_enum)]
+  preset: Option<Preset>,
+
+  /// POST the JSON report to this URL
+  #[arg(long, value_name = "URL")]
+  post_to: Option<String>,
+
+  /// Keep files without findings in the JSON output
+  #[arg(long)]
+  include_clean: bool,
+
+  /// Don't open the HTML report; print each written file as a JSON line
+  #[arg(long)]
+  print_output_path: bool,
+
+  /// Leave the target browsers panel and icons out of the HTML report
+  #[arg(long)]
+  concise_html: bool,
+
+  /// Organize JSON and HTML output per file or per feature
+  #[arg(long, value_enum, default_value_t = GroupBy::File)]
+  group_by: GroupBy,
+
+  /// Fixed report time in Unix seconds, for reproducible output names.
+  /// Defaults to SOURCE_DATE_EPOCH when that is set
+  #[arg(long, value_name = "SECONDS")]
+  timestamp: Option<i64>,
+
+  /// JSON file of extra method detectors: [{"method": "...", "key": "..."}]
+  #[arg(long, value_name = "FILE")]
+  detectors: Option<String>,
+
+  /// Suggest `??` where `a || default` may be meant as a nullish fallback
+  #[arg(long)]
+  suggest_nullish: bool,
+
+  /// JSON file of exact target versions, e.g. {"chrome": "91"}, used
+  /// instead of browserslist
+  #[arg(long, value_name = "FILE", conflicts_with = "preset")]
+  targets: Option<String>,
+
+  /// Browserslist query to use instead of .browserslistrc, e.g.
+  /// "last 2 versions, not dead"
+  #[arg(long, value_name = "QUERY", conflicts_with_all = ["preset", "targets"])]
+  browsers: Option<String>,
+
+  /// Stop after this many features have been found
+  #[arg(long, alias = "head", value_name = "N")]
+  max_findings: Option<usize>,
+
+  /// Skip resolved inputs matching this glob, e.g. "**/*.min.js" (repeatable)
+  #[arg(long = "ignore-pattern", value_name = "GLOB")]
+  ignore_patterns: Vec<String>,
+
+  /// File extensions to pick up from directories and globs
+  /// [default: js,mjs,cjs,jsx]
+  #[arg(long = "ext", value_name = "EXT", value_delimiter = ',')]
+  extensions: Vec<String>,
+
+  /// Parse every input as a script or a module regardless of extension
+  #[arg(long, value_name = "script|module")]
+  source_type: Option<SourceKind>,
+
+  /// Where downloads and BCD data are cached. Defaults to $JSCO_CACHE_DIR,
+  /// then the OS cache directory
+  #[arg(long, value_name = "DIR")]
+  cache_dir: Option<String>,
+
+  /// Never touch the network; use cached or local BCD data and cached URLs
+  #[arg(long)]
+  offline: bool,
+
+  /// Local BCD snapshot (@mdn/browser-compat-data data.json) to use instead
+  /// of downloading one
+  #[arg(long, value_name = "FILE")]
+  bcd_file: Option<String>,
+
+  /// Download URL inputs again instead of using cached copies
+  #[arg(long, alias = "no-cache")]
+  refresh: bool,
+
+  /// Exit code used when the inputs match no files
+  #[arg(long, value_name = "CODE", default_value_t = 2)]
+  no_files_exit_code: i32,
+
+  /// Describe a feature (by name or BCD key) instead of analyzing files
+  #[arg(long, value_name = "FEATURE", value_parser = parse_feature)]
+  explain: Option<JsFeature>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Combine JSON reports from separate runs into one document
+  Merge {
+    /// JSON documents written by `--format json`
+    #[arg(required = true)]
+    documents: Vec<String>,
+  },
+}
+
+/// Parses `--max-bcd-age` days into a duration, rejecting counts too large
+/// to represent.
+pub fn parse_bcd_age(days: &str) -> Result<Duration, String> {
+  let days: u64 = days
+    .parse()
+    .map_err(|e: std::num::ParseIntError| e.to_string())?;
+  days
+    .checked_mul(24 * 60 * 60)
+    .map(Duration::from_secs)
+    .ok_or_else(|| format!("{} days is too long", days))
+}
+
+fn parse_feature(name: &str) -> Result<JsFeature, String> {
+  JsFeature::from_name(name).ok_or_else(|| format!("unknown feature: {}", name))
 }
 
-pub async fn run(arguments: Vec<String>) {
+#[derive(Debug, Deserialize)]
+struct DetectorEntry {
+  method: String,
+  key: String,
+}
+
+fn load_detectors(path: &str) -> Result<Vec<CustomMethod>, String> {
+  let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+  let entries: Vec<DetectorEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+  Ok(
+    entries
+      .into_iter()
+      .map(|entry| CustomMethod::new(entry.method, &entry.key))
+      .collect(),
+  )
+}
+
+/// Runs the CLI and returns the process exit code.
+pub async fn run(arguments: Vec<String>) -> i32 {
   let _ = CLIENT.get_or_init(|| Client::new());
-  let _ = ALLOCATOR.get_or_init(|| Arc::new(Allocator::default()));
 
   let args = Args::parse_from(arguments);
+  if let Some(dir) = &args.cache_dir {
+    jsco::cache::set_dir(dir);
+  }
+  if let Some(path) = &args.bcd_file {
+    jsco::bcd::use_bcd_file(path);
+  }
+  jsco::download::set_offline(args.offline);
+  jsco::set_quiet(args.quiet);
+  let timestamp = args.timestamp.or_else(|| {
+    std::env::var("SOURCE_DATE_EPOCH")
+      .ok()
+      .and_then(|epoch| epoch.parse().ok())
+  });
+  if let Some(Command::Merge { documents }) = &args.command {
+    let settings = OutputSettings {
+      print_output_path: args.print_output_path,
+      timestamp,
+      ..OutputSettings::default()
+    };
+    return run_merge(documents, &settings);
+  }
+  if let Some(feature) = args.explain {
+    jsco::cache::init();
+    println!("{}", explain(feature));
+    return 0;
+  }
   let inputs = args.inputs;
 
-  let output_format = match args.format.to_lowercase().as_str() {
-    "json" => OutputFormat::Json,
-    _ => OutputFormat::HTML,
+  let mut output_formats = Vec::new();
+  for format in &args.format {
+    let output_format = match format.to_lowercase().as_str() {
+      "json" => OutputFormat::Json,
+      "keys" => OutputFormat::Keys,
+      "sarif" => OutputFormat::Sarif,
+      "markdown" | "md" => OutputFormat::Markdown,
+      "junit" => OutputFormat::JUnit,
+      "console" | "text" => OutputFormat::Text,
+      _ => OutputFormat::HTML,
+    };
+    if !output_formats.contains(&output_format) {
+      output_formats.push(output_format);
+    }
+  }
+
+  let mut enabled: HashSet<JsFeature> = args.enable.into_iter().collect();
+  if let Some(preset) = args.preset {
+    enabled.extend(preset.enabled_features());
+  }
+
+  let custom_methods = match &args.detectors {
+    Some(path) => match load_detectors(path) {
+      Ok(detectors) => detectors,
+      Err(err) => {
+        eprintln!("Failed to load detectors from {}: {}", path, err);
+        return 1;
+      }
+    },
+    None => Vec::new(),
   };
 
-  let cache_dir = PathBuf::from(CACHE_DIR);
-  if !cache_dir.exists() {
-    let _ = fs::create_dir(&cache_dir);
+  let options = Options {
+    quiet: args.quiet,
+    enabled,
+    chain_analysis: args.chain_analysis,
+    profile: args.profile,
+    max_bcd_age: args.max_bcd_age,
+    custom_methods,
+    nullish_advisory: args.suggest_nullish,
+    max_findings: args.max_findings,
+    ignore_patterns: args.ignore_patterns,
+    extensions: args
+      .extensions
+      .iter()
+      .map(|ext| ext.trim_start_matches('.').to_string())
+      .collect(),
+    refresh: args.refresh,
+    source_kind: args.source_type,
+    read_package_json: true,
+  };
+  let targets = match (&args.targets, &args.browsers) {
+    (Some(path), _) => match load_targets_file(path) {
+      Ok(targets) => targets,
+      Err(err) => {
+        eprintln!("Failed to load targets from {}: {}", path, err);
+        return 1;
+      }
+    },
+    (None, Some(query)) => match resolve_query(query) {
+      Ok(targets) => targets,
+      Err(err) => {
+        eprintln!("Invalid browserslist query {:?}: {}", query, err);
+        return 1;
+      }
+    },
+    (None, None) => resolve_targets(args.preset),
+  };
+  let target_source = match (args.targets, args.browsers, args.preset) {
+    (Some(path), _, _) => TargetSource::File(path),
+    (None, Some(query), _) => TargetSource::Query(query),
+    (None, None, Some(preset)) => TargetSource::Preset(preset),
+    (None, None, None) => TargetSource::Browserslistrc,
+  };
+  let (mut reports, truncated) = match jsco_with_options(inputs, options).await {
+    Ok(analysis) => (analysis.reports, analysis.truncated),
+    Err(err) => {
+      eprintln!("{}", err);
+      return 1;
+    }
+  };
+  if reports.is_empty() {
+    return args.no_files_exit_code;
+  }
+  // Sources that matched but couldn't be read or analyzed come back as failed
+  // reports, already listed on stderr; they fail the run but aren't output
+  let matched = reports.len();
+  reports.retain(|report| report.error.is_none());
+  let failed = matched - reports.len();
+
+  let severities: SeverityMap = args.severity.into_iter().collect();
+  let mut error_count = 0;
+  let mut incompatible_count = 0;
+  for report in &mut reports {
+    for feature in &mut report.found_features {
+      feature.severity = Some(severity_for(feature, &severities, &targets));
+    }
+    report
+      .found_features
+      .retain(|feature| feature.severity != Some(Severity::Off));
+    report.required_versions = report.min_required_versions();
+    // Unsupported features default to error too, but only fail the run
+    // under --fail-on-incompatible
+    error_count += report
+      .found_features
+      .iter()
+      .filter(|feature| severities.get(&feature.feat_type) == Some(&Severity::Error))
+      .count();
+    incompatible_count += report
+      .found_features
+      .iter()
+      .filter(|feature| {
+        !feature.feat_type.is_informational() && !is_feature_supported(feature, &targets)
+      })
+      .count();
   }
 
-  let reports = jsco(inputs).await;
-  reports.output(output_format);
+  if args.list_unsupported {
+    print_unsupported(&unsupported_by_feature(&reports, &targets));
+  }
+
+  let settings = OutputSettings {
+    include_clean: args.include_clean,
+    print_output_path: args.print_output_path,
+    concise_html: args.concise_html,
+    group_by: args.group_by,
+    timestamp,
+    target_source,
+    truncated,
+  };
+  let started = Instant::now();
+  reports.output(&output_formats, &targets, &settings);
+  if args.profile {
+    let mut profile = Profile::default();
+    profile.add("output", started.elapsed());
+    profile.print();
+  }
+
+  if let Some(url) = &args.post_to {
+    match post_report(url, &reports, &targets, &settings).await {
+      Ok(()) => eprintln!("Report posted to: {}", url),
+      Err(err) => {
+        eprintln!("Failed to post report to {}: {}", url, err);
+        return 1;
+      }
+    }
+  }
+
+  let mut exit_code = 0;
+  if failed > 0 {
+    eprintln!("{} file(s) couldn't be analyzed", failed);
+    exit_code = 1;
+  }
+  if error_count > 0 {
+    eprintln!("{} feature(s) mapped to error severity", error_count);
+    exit_code = 1;
+  }
+  if args.fail_on_incompatible && incompatible_count > 0 {
+    eprintln!(
+      "{} feature(s) not supported by the target browsers",
+      incompatible_count
+    );
+    exit_code = 1;
+  }
+  exit_code
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OutputFormat {
   HTML,
   Json,
+  /// Detected BCD keys, one per line on stdout
+  Keys,
+  /// Per-file findings printed to stdout, nothing written
+  Text,
+  /// SARIF 2.1.0 log for code scanning
+  Sarif,
+  /// Per-file feature tables for pull request review
+  Markdown,
+  /// JUnit XML where unsupported features are failing test cases
+  JUnit,
+}
+
+/// How JSON and HTML output is organized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+  /// Each file with the features it uses
+  #[default]
+  File,
+  /// Each feature with the files that use it
+  Feature,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OutputSettings {
+  /// Keep reports without findings in JSON output
+  pub include_clean: bool,
+  /// Print `{"format":..,"path":..}` per written file instead of opening HTML
+  pub print_output_path: bool,
+  /// Findings-only HTML without the target browsers panel
+  pub concise_html: bool,
+  pub group_by: GroupBy,
+  /// Fixed report time in Unix seconds instead of the current time
+  pub timestamp: Option<i64>,
+  /// Where the targets came from, shown in the HTML targets panel
+  pub target_source: TargetSource,
+  /// The run stopped at `--max-findings` before analyzing every input
+  pub truncated: bool,
+}
+
+impl OutputSettings {
+  /// The time stamped on output files: the fixed timestamp if set, otherwise
+  /// the current local time.
+  pub fn generated_at(&self) -> DateTime<FixedOffset> {
+    self
+      .timestamp
+      .and_then(|secs| DateTime::from_timestamp(secs, 0))
+      .map(|time| time.fixed_offset())
+      .unwrap_or_else(|| Local::now().fixed_offset())
+  }
 }
 
 pub trait ReportOutput {
-  fn output(&self, format: OutputFormat);
+  fn output(&self, formats: &[OutputFormat], targets: &[Target], settings: &OutputSettings) {
+    for format in formats {
+      self.output_format(format.clone(), targets, settings);
+    }
+  }
+
+  fn output_format(&self, format: OutputFormat, targets: &[Target], settings: &OutputSettings);
+}
+
+/// Writes `contents` to `jsco-output/report_<timestamp>.<extension>` and
+/// returns the path it was written to.
+fn write_output(extension: &str, contents: &str, settings: &OutputSettings) -> Option<String> {
+  // Create output directory if it doesn't exist
+  let output_dir = "jsco-output";
+  let _ = fs::create_dir_all(output_dir);
+
+  let timestamp = settings.generated_at().format("%Y%m%d_%H%M%S");
+  let output_file = format!("{}/report_{}.{}", output_dir, timestamp, extension);
+
+  let Ok(mut file) = fs::File::create(&output_file) else {
+    eprintln!("Failed to create output file");
+    return None;
+  };
+  if file.write_all(contents.as_bytes()).is_err() {
+    eprintln!("Failed to write report to file");
+    return None;
+  }
+  eprintln!("Report saved to: {}", output_file);
+  Some(output_file)
+}
+
+/// Machine-readable notice of a written report, for wrapping scripts.
+fn print_output_path(format: &str, path: &str) {
+  println!("{}", serde_json::json!({ "format": format, "path": path }));
 }
 
 impl ReportOutput for Reports {
-  fn output(&self, format: OutputFormat) {
+  fn output_format(&self, format: OutputFormat, targets: &[Target], settings: &OutputSettings) {
     match format {
       OutputFormat::HTML => {
-        let browsers = execute(&Opts::default()).unwrap_or_default();
-        let mut chrome_versions = Vec::new();
-        let mut firefox_versions = Vec::new();
-        let mut safari_versions = Vec::new();
-        let mut edge_versions = Vec::new();
-        let mut other_browsers = Vec::new();
-        for browser in &browsers {
-          let name = browser.name().to_lowercase();
-          match name.as_str() {
-            "chrome" | "and_chr" | "chrome android" => {
-              chrome_versions.push(browser.version().to_string())
-            }
-            "firefox" | "firefox android" => firefox_versions.push(browser.version().to_string()),
-            "safari" | "ios_saf" => safari_versions.push(browser.version().to_string()),
-            "edge" => edge_versions.push(browser.version().to_string()),
-            _ => other_browsers.push((browser.name(), browser.version())),
+        let html_output = render_html(
+          self,
+          targets,
+          &settings.target_source,
+          settings.concise_html,
+          settings.group_by == GroupBy::Feature,
+        );
+        if let Some(output_file) = write_output("html", &html_output, settings) {
+          if settings.print_output_path {
+            print_output_path("html", &output_file);
+          } else {
+            let _ = open::that(output_file);
           }
         }
+      }
 
-        // Group and sort versions
-        let format_versions = |versions: &[String]| -> String {
-          let mut versions = versions.to_vec();
-          versions.sort();
-          versions.join(", ")
-        };
-
-        let default_version = "0".to_string();
-        chrome_versions.sort();
-        firefox_versions.sort();
-        safari_versions.sort();
-        edge_versions.sort();
-
-        let html_output = html! {
-          html class="bg-slate-50" {
-            head {
-              title { "JavaScript Compatibility Report" }
-              script src="https://cdn.tailwindcss.com" {}
-              meta charset="UTF-8" {}
-              meta name="viewport" content="width=device-width, initial-scale=1.0" {}
-              style type="text/css" {
-                (r#"
-                summary {
-                  list-style: none;
-                }
-                summary::-webkit-details-marker {
-                  display: none;
-                }
-                summary::marker {
-                  display: none;
-                }
-                .code-block {
-                  background-image: linear-gradient(to bottom, #f8fafc, #f1f5f9);
-                }
-                details[open] summary ~ * {
-                  animation: sweep .3s ease-in-out;
-                }
-                @keyframes sweep {
-                  0%    {opacity: 0; transform: translateY(-10px)}
-                  100%  {opacity: 1; transform: translateY(0)}
-                }
-                .feature-card {
-                  transition: all 0.2s ease-in-out;
-                }
-                .feature-card:hover {
-                  transform: translateY(-2px);
-                  box-shadow: 0 8px 24px -12px rgba(0, 0, 0, 0.15);
-                }
-                "#)
-              }
-            }
-            body class="min-h-screen p-4 md:p-8 bg-gradient-to-br from-slate-50 to-slate-100/50" {
-              div class="max-w-6xl mx-auto" {
-                div class="text-center mb-16" {
-                  h1 class="text-4xl font-bold text-slate-900 mb-4 bg-clip-text text-transparent bg-gradient-to-r from-blue-600 to-blue-800" {
-                    "JavaScript Compatibility Report"
-                  }
-                  p class="text-lg text-slate-600 max-w-2xl mx-auto" {
-                    "Compatibility analysis based on MDN browser-compat-data"
-                  }
-                }
-
-                div class="mb-12 bg-white rounded-xl shadow-sm border border-slate-200/60 p-6" {
-                  h2 class="text-lg font-semibold text-slate-800 mb-4" {
-                    "Target Browsers"
-                    span class="ml-2 text-sm font-normal text-slate-500" {
-                      "(from .browserslistrc)"
-                    }
-                  }
-                  div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4" {
-                    @if !chrome_versions.is_empty() {
-                      div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
-                        div class="flex items-center gap-2 text-slate-700 mb-3" {
-                          span class="font-medium" { "Chrome" }
-                        }
-                        div class="space-y-1.5" {
-                          div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
-                          div class="text-sm text-slate-700 font-mono" {
-                            @let min_version = chrome_versions.iter().min().unwrap_or(&default_version);
-                            @let max_version = chrome_versions.iter().max().unwrap_or(&default_version);
-                            @if min_version == max_version {
-                              (format!("v{}", min_version))
-                            } @else {
-                              (format!("v{} - v{}", min_version, max_version))
-                            }
-                          }
-                          div class="text-xs text-slate-500 mt-1" {
-                            (format!("Including: {}", format_versions(&chrome_versions)))
-                          }
-                        }
-                      }
-                    }
-
-                    @if !firefox_versions.is_empty() {
-                      div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
-                        div class="flex items-center gap-2 text-slate-700 mb-3" {
-                          span class="font-medium" { "Firefox" }
-                        }
-                        div class="space-y-1.5" {
-                          div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
-                          div class="text-sm text-slate-700 font-mono" {
-                            @let min_version = firefox_versions.iter().min().unwrap_or(&default_version);
-                            @let max_version = firefox_versions.iter().max().unwrap_or(&default_version);
-                            @if min_version == max_version {
-                              (format!("v{}", min_version))
-                            } @else {
-                              (format!("v{} - v{}", min_version, max_version))
-                            }
-                          }
-                          div class="text-xs text-slate-500 mt-1" {
-                            (format!("Including: {}", format_versions(&firefox_versions)))
-                          }
-                        }
-                      }
-                    }
-
-                    @if !safari_versions.is_empty() {
-                      div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
-                        div class="flex items-center gap-2 text-slate-700 mb-3" {
-                          span class="font-medium" { "Safari" }
-                        }
-                        div class="space-y-1.5" {
-                          div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
-                          div class="text-sm text-slate-700 font-mono" {
-                            @let min_version = safari_versions.iter().min().unwrap_or(&default_version);
-                            @let max_version = safari_versions.iter().max().unwrap_or(&default_version);
-                            @if min_version == max_version {
-                              (format!("v{}", min_version))
-                            } @else {
-                              (format!("v{} - v{}", min_version, max_version))
-                            }
-                          }
-                          div class="text-xs text-slate-500 mt-1" {
-                            (format!("Including: {}", format_versions(&safari_versions)))
-                          }
-                        }
-                      }
-                    }
-
-                    @if !edge_versions.is_empty() {
-                      div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
-                        div class="flex items-center gap-2 text-slate-700 mb-3" {
-                          span class="font-medium" { "Edge" }
-                        }
-                        div class="space-y-1.5" {
-                          div class="text-xs font-medium uppercase tracking-wider text-slate-500" { "Required Versions" }
-                          div class="text-sm text-slate-700 font-mono" {
-                            @let min_version = edge_versions.iter().min().unwrap_or(&default_version);
-                            @let max_version = edge_versions.iter().max().unwrap_or(&default_version);
-                            @if min_version == max_version {
-                              (format!("v{}", min_version))
-                            } @else {
-                              (format!("v{} - v{}", min_version, max_version))
-                            }
-                          }
-                          div class="text-xs text-slate-500 mt-1" {
-                            (format!("Including: {}", format_versions(&edge_versions)))
-                          }
-                        }
-                      }
-                    }
-
-                    @if !other_browsers.is_empty() {
-                      div class="p-4 rounded-lg bg-gradient-to-br from-slate-50 to-white border border-slate-200" {
-                        div class="flex items-center gap-2 text-slate-700 mb-3" {
-                          span class="font-medium" { "Other" }
-                        }
-                        div class="space-y-2" {
-                          @for (name, version) in &other_browsers {
-                            div class="text-sm text-slate-600" {
-                              span class="font-medium" { (name) }
-                              span class="font-mono ml-2" { (format!("v{}", version)) }
-                            }
-                          }
-                        }
-                      }
-                    }
-                  }
-                }
-
-                @for report in self {
-                  @if !report.found_features.is_empty() {
-                    div class="space-y-8 mb-12" {
-                      h3 class="text-lg font-medium text-slate-700 mb-6 pb-2 border-b border-slate-200" {
-                        span class="inline-flex items-center gap-2" {
-                          svg xmlns="http://www.w3.org/2000/svg" class="w-5 h-5 text-slate-400" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
-                            path d="M13 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V9z" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                            path d="M13 2v7h7" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                          }
-                          (report.path)
-                        }
-                      }
-                      @for feature in &report.found_features {
-                        div class="feature-card bg-white rounded-xl shadow-sm border border-slate-200/60 p-6 transition-all" {
-                          div class="flex flex-col md:flex-row md:items-start md:justify-between mb-6" {
-                            div {
-                              h2 class="text-xl font-semibold text-blue-600/90 mb-3" {
-                                (format!("{:?}", feature.feat_type))
-                              }
-                              a class="text-sm text-slate-500 hover:text-blue-500 hover:underline inline-flex items-center gap-1.5 group"
-                                href=(feature.mdn_url) target="_blank" rel="noopener" {
-                                svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4 transition-transform group-hover:translate-x-0.5" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
-                                  path d="M10 6H6a2 2 0 00-2 2v10a2 2 0 002 2h10a2 2 0 002-2v-4M14 4h6m0 0v6m0-6L10 14" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                                }
-                                span { "MDN Documentation" }
-                              }
-                            }
-                            div class="flex flex-wrap gap-2 mt-4 md:mt-0" {
-                              @let support = feature.support.lock().unwrap();
-                              @let mut browser_info: Vec<_> = support.iter().collect();
-                              @let _ = browser_info.sort_by(|a, b| a.0.cmp(b.0));
-                              @let browsers = execute(&Opts::default()).unwrap_or_default();
-
-                              @for (browser, version) in &browser_info {
-                                @let is_compatible = is_supported(browser, version, browsers.as_slice());
-                                div class=(if is_compatible {
-                                  "inline-flex items-center px-3 py-1.5 rounded-full text-sm bg-green-50 text-green-700 border border-green-100 shadow-sm hover:bg-green-100 transition-colors"
-                                } else {
-                                  "inline-flex items-center px-3 py-1.5 rounded-full text-sm bg-red-50 text-red-700 border border-red-100 shadow-sm hover:bg-red-100 transition-colors"
-                                }) {
-                                  span class="mr-1.5" {
-                                    @if is_compatible {
-                                      svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
-                                        path d="M20 6L9 17l-5-5" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                                      }
-                                    } @else {
-                                      svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4" viewBox="0 0 24 24" fill="none" stroke="currentColor" {
-                                        path d="M18 6L6 18M6 6l12 12" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                                      }
-                                    }
-                                  }
-                                  (format!("{} ≥ {}", browser, version))
-                                }
-                              }
-                            }
-                          }
-
-                          div class="mt-6" {
-                            details class="group" {
-                              summary class="text-lg font-medium text-slate-800 cursor-pointer hover:text-blue-600 transition-colors" {
-                                span class="inline-flex items-center gap-2" {
-                                  svg xmlns="http://www.w3.org/2000/svg"
-                                    class="w-5 h-5 text-slate-400 group-open:rotate-90 transition-transform"
-                                    viewBox="0 0 24 24" fill="none" stroke="currentColor" {
-                                    path d="M9 5l7 7-7 7" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
-                                  }
-                                  (format!("Found in {} locations", feature.found_in.len()))
-                                }
-                              }
-                              div class="space-y-4 mt-4" {
-                                @for span in &feature.found_in {
-                                  div class="rounded-lg border border-slate-200 overflow-hidden transition-all hover:border-slate-300" {
-                                    div class="flex items-center justify-between px-4 py-2.5 bg-slate-50 text-sm text-slate-600 border-b border-slate-200" {
-                                      span class="font-medium" {
-                                        (format!("Lines {}-{}", span.start, span.end))
-                                      }
-                                    }
-                                    div class="code-block p-4 font-mono text-sm overflow-x-auto" {
-                                      code {(span.source_text(&report.source_code))}
-                                    }
-                                  }
-                                }
-                              }
-                            }
-                          }
-                        }
-                      }
-                    }
-                  }
-                }
-              }
+      OutputFormat::Json => {
+        if let Ok(json) = serde_json::to_string_pretty(&JsonDocument::new(self, targets, settings))
+        {
+          if let Some(output_file) = write_output("json", &json, settings) {
+            if settings.print_output_path {
+              print_output_path("json", &output_file);
             }
           }
-        };
+        } else {
+          eprintln!("Failed to serialize report to JSON");
+        }
+      }
 
-        // Create output directory if it doesn't exist
-        let output_dir = "jsco-output";
-        let _ = fs::create_dir_all(output_dir);
+      OutputFormat::Keys => {
+        for key in detected_keys(self) {
+          println!("{}", key);
+        }
+      }
 
-        // Generate timestamp for the filename
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let output_file = format!("{}/report_{}.html", output_dir, timestamp);
+      OutputFormat::Text => println!("{}", render_text(self)),
 
-        // Write the HTML to file
-        if let Ok(mut file) = fs::File::create(&output_file) {
-          if let Ok(_) = file.write_all(html_output.into_string().as_bytes()) {
-            println!("Report saved to: {}", output_file);
-          } else {
-            eprintln!("Failed to write report to file");
+      OutputFormat::Markdown => {
+        let markdown = render_markdown(self);
+        if let Some(output_file) = write_output("md", &markdown, settings) {
+          if settings.print_output_path {
+            print_output_path("markdown", &output_file);
           }
-        } else {
-          eprintln!("Failed to create output file");
         }
+      }
 
-        // open the file
-        let _ = open::that(output_file);
+      OutputFormat::JUnit => {
+        let junit = render_junit(self, targets);
+        if let Some(output_file) = write_output("xml", &junit, settings) {
+          if settings.print_output_path {
+            print_output_path("junit", &output_file);
+          }
+        }
       }
 
-      OutputFormat::Json => {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-          let output_dir = "jsco-output";
-          let _ = fs::create_dir_all(output_dir);
-
-          let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-          let output_file = format!("{}/report_{}.json", output_dir, timestamp);
-
-          if let Ok(mut file) = fs::File::create(&output_file) {
-            if let Ok(_) = file.write_all(json.as_bytes()) {
-              println!("Report saved to: {}", output_file);
-            } else {
-              eprintln!("Failed to write report to file");
+      OutputFormat::Sarif => {
+        if let Ok(sarif) = serde_json::to_string_pretty(&render_sarif(self, targets)) {
+          if let Some(output_file) = write_output("sarif", &sarif, settings) {
+            if settings.print_output_path {
+              print_output_path("sarif", &output_file);
             }
-          } else {
-            eprintln!("Failed to create output file");
           }
         } else {
-          eprintln!("Failed to serialize report to JSON");
+          eprintln!("Failed to serialize report to SARIF");
+        }
+      }
+    }
+  }
+}
+
+/// `jsco merge`: combines JSON documents into one and writes it like
+/// `--format json` would, with a `summary` of the combined reports.
+fn run_merge(paths: &[String], settings: &OutputSettings) -> i32 {
+  let mut documents = Vec::new();
+  for path in paths {
+    let document = fs::read_to_string(path)
+      .map_err(|e| e.to_string())
+      .and_then(|data| parse_document(&data));
+    match document {
+      Ok(document) => documents.push(document),
+      Err(err) => {
+        eprintln!("Failed to read report {}: {}", path, err);
+        return 1;
+      }
+    }
+  }
+
+  let count = documents.len();
+  let merged = merge_documents(documents);
+  let settings = &OutputSettings {
+    truncated: merged.truncated,
+    ..settings.clone()
+  };
+  let totals = summary(&merged.reports);
+  eprintln!(
+    "Merged {} documents: {} files, {} features",
+    count, totals["files"], totals["findings"]
+  );
+  let Ok(mut document) = serde_json::to_value(JsonDocument::new(
+    &merged.reports,
+    &merged.targets,
+    settings,
+  )) else {
+    eprintln!("Failed to serialize report to JSON");
+    return 1;
+  };
+  document["summary"] = totals;
+  let Ok(json) = serde_json::to_string_pretty(&document) else {
+    eprintln!("Failed to serialize report to JSON");
+    return 1;
+  };
+  match write_output("json", &json, settings) {
+    Some(output_file) => {
+      if settings.print_output_path {
+        print_output_path("json", &output_file);
+      }
+      0
+    }
+    None => 1,
+  }
+}
+
+/// Top-level JSON output: the findings plus the browser targets they were
+/// evaluated against, so the document is self-describing.
+#[derive(Debug, Serialize)]
+struct JsonDocument<'a> {
+  generated_at: String,
+  /// Whether `--max-findings` cut the run short, leaving files unanalyzed
+  truncated: bool,
+  targets: BTreeMap<String, Vec<String>>,
+  /// Feature key -> target browsers that don't support it
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  unsupported: BTreeMap<String, Vec<String>>,
+  /// Browser -> oldest version that runs every file
+  required_versions: BrowserSupport,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  reports: Option<Vec<&'a Report>>,
+  /// Set instead of `reports` when grouping by feature
+  #[serde(skip_serializing_if = "Option::is_none")]
+  features: Option<Vec<FeatureGroup<'a>>>,
+}
+
+impl<'a> JsonDocument<'a> {
+  /// Reports without findings or diagnostics are left out unless
+  /// `include_clean` is set, since they'd carry little but source code.
+  fn new(reports: &'a Reports, targets: &[Target], settings: &OutputSettings) -> Self {
+    let by_feature = settings.group_by == GroupBy::Feature;
+    Self {
+      generated_at: settings.generated_at().to_rfc3339(),
+      truncated: settings.truncated,
+      targets: group_targets(targets),
+      unsupported: unsupported_by_feature(reports, targets),
+      required_versions: min_required_versions(reports),
+      reports: (!by_feature).then(|| {
+        reports
+          .iter()
+          .filter(|report| settings.include_clean || !report.is_clean())
+          .collect()
+      }),
+      features: by_feature.then(|| group_by_feature(reports)),
+    }
+  }
+}
+
+const POST_ATTEMPTS: u64 = 3;
+
+/// POSTs the JSON document to `url`, retrying server errors and failed
+/// connections with a short backoff. A 4xx response fails right away, since
+/// sending the same body again won't change the answer.
+async fn post_report(
+  url: &str,
+  reports: &Reports,
+  targets: &[Target],
+  settings: &OutputSettings,
+) -> Result<(), String> {
+  let body = serde_json::to_string(&JsonDocument::new(reports, targets, settings))
+    .map_err(|e| e.to_string())?;
+  let client = CLIENT.get_or_init(|| Client::new());
+
+  let mut last_error = String::new();
+  for attempt in 1..=POST_ATTEMPTS {
+    let result = client
+      .post(url)
+      .header(reqwest::header::CONTENT_TYPE, "application/json")
+      .body(body.clone())
+      .send()
+      .await;
+    let retry = match result {
+      Ok(res) if res.status().is_success() => return Ok(()),
+      Ok(res) => {
+        last_error = format!("server responded with {}", res.status());
+        res.status().is_server_error()
+      }
+      Err(err) => {
+        last_error = err.to_string();
+        err.is_connect() || err.is_timeout()
+      }
+    };
+    if !retry {
+      break;
+    }
+    if attempt < POST_ATTEMPTS {
+      tokio::time::sleep(Duration::from_millis(500 * attempt)).await;
+    }
+  }
+  Err(last_error)
+}
+
+/// The sorted, deduplicated BCD keys of every feature found in `reports`.
+pub fn detected_keys(reports: &Reports) -> BTreeSet<&str> {
+  reports
+    .iter()
+    .flat_map(|report| &report.found_features)
+    .map(|feature| feature.feat_type.key())
+    .collect()
+}
+
+/// Groups resolved browserslist targets into browser name -> versions.
+pub fn group_targets(browsers: &[Target]) -> BTreeMap<String, Vec<String>> {
+  let mut targets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+  for browser in browsers {
+    targets
+      .entry(browser.name().to_string())
+      .or_default()
+      .push(browser.version().to_string());
+  }
+  targets
+}
+
+/// For each detected feature, the target browsers (e.g. `safari 12`) whose
+/// version predates the feature's support. Informational features never
+/// block a target.
+pub fn unsupported_by_feature(
+  reports: &Reports,
+  targets: &[Target],
+) -> BTreeMap<String, Vec<String>> {
+  let mut unsupported: BTreeMap<String, Vec<String>> = BTreeMap::new();
+  for feature in reports
+    .iter()
+    .flat_map(|report| &report.found_features)
+    .filter(|feature| !feature.feat_type.is_informational())
+  {
+    let support = feature.support.lock().unwrap();
+    for target in targets {
+      let blocked = support
+        .iter()
+        .any(|(browser, version)| !is_supported(browser, version, std::slice::from_ref(target)));
+      if blocked {
+        let name = format!("{} {}", target.name(), target.version());
+        let blockers = unsupported
+          .entry(feature.feat_type.key().to_string())
+          .or_default();
+        if !blockers.contains(&name) {
+          blockers.push(name);
         }
       }
     }
   }
+  unsupported
 }
 
-pub fn is_supported(browser: &str, version: &str, browsers: &[Distrib]) -> bool {
+fn print_unsupported(unsupported: &BTreeMap<String, Vec<String>>) {
+  if unsupported.is_empty() {
+    println!("All detected features are supported by the target browsers");
+    return;
+  }
+  let width = unsupported.keys().map(|key| key.len()).max().unwrap_or(0);
+  println!("Unsupported features:");
+  for (key, blockers) in unsupported {
+    println!(
+      "  {:<width$}  blocked by {}",
+      key,
+      blockers.join(", "),
+      width = width
+    );
+  }
+}
+
+pub fn is_supported(browser: &str, version: &str, browsers: &[Target]) -> bool {
   // If no browsers are specified, consider it supported
   if browsers.is_empty() {
     return true;
@@ -417,7 +807,7 @@ pub fn is_supported(browser: &str, version: &str, browsers: &[Distrib]) -> bool
   let browser_name = browser.to_lowercase();
 
   // Map our internal names to browserslist names
-  let matches_browser = |b: &Distrib| {
+  let matches_browser = |b: &Target| {
     let b_name = b.name().to_lowercase();
     match browser_name.as_str() {
       "chrome" => matches!(b_name.as_str(), "and_chr" | "chrome" | "chrome android"),
@@ -439,22 +829,22 @@ pub fn is_supported(browser: &str, version: &str, browsers: &[Distrib]) -> bool
     return true;
   }
 
-  // Skip if version is "true" (meaning always supported)
-  if version == "true" {
-    return true;
+  // "true" means supported in every version, "false" in none and
+  // "preview" only in unreleased builds
+  match version {
+    "true" => return true,
+    "false" | "preview" => return false,
+    _ => {}
   }
 
-  // Get our major version number
-  let our_version = version.split('.').next().unwrap_or("0");
-  let our_version: u32 = our_version.parse().unwrap_or(0);
+  let our_version = parse_version(version);
 
   // Check against all matching browsers
   for browser in matching_browsers {
-    let their_version = browser.version().split('.').next().unwrap_or("0");
-    let their_version: u32 = their_version.parse().unwrap_or(0);
+    let their_version = parse_version(browser.version());
 
     // If our required version is higher than their version, it's not supported
-    if our_version > their_version {
+    if compare_versions(&our_version, &their_version) == Ordering::Greater {
       return false;
     }
   }
@@ -3,5 +3,6 @@ use std::env;
 
 #[tokio::main]
 async fn main() {
-  run(env::args().collect()).await;
+  let code = run(env::args().collect()).await;
+  std::process::exit(code);
 }
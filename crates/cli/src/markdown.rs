@@ -0,0 +1,80 @@
+use jsco::feature::es_year_label;
+use jsco::report::Reports;
+
+/// Escapes text for a Markdown table cell.
+fn cell(text: &str) -> String {
+  text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Renders a reviewable Markdown report: run totals, then a table of
+/// features per file.
+pub fn render_markdown(reports: &Reports) -> String {
+  let with_features = reports
+    .iter()
+    .filter(|report| !report.found_features.is_empty())
+    .collect::<Vec<_>>();
+  let total_features = with_features
+    .iter()
+    .map(|report| report.found_features.len())
+    .sum::<usize>();
+
+  let mut lines = vec![
+    "# JavaScript Compatibility Report".to_string(),
+    String::new(),
+    "## Summary".to_string(),
+    String::new(),
+    format!("- Total files processed: {}", reports.len()),
+    format!("- Files with features: {}", with_features.len()),
+    format!("- Total features found: {}", total_features),
+  ];
+
+  for report in with_features {
+    lines.push(String::new());
+    lines.push(format!("## `{}`", report.path));
+    lines.push(String::new());
+    lines.push("| Feature | Standard | Lines | Minimum versions | Docs |".to_string());
+    lines.push("| --- | --- | --- | --- | --- |".to_string());
+
+    let mut features = report.found_features.iter().collect::<Vec<_>>();
+    features.sort_by_key(|feature| feature.feat_type.key());
+    for feature in features {
+      let standard = feature
+        .feat_type
+        .es_year()
+        .map(es_year_label)
+        .unwrap_or_else(|| "-".to_string());
+      let ranges = feature
+        .locations
+        .iter()
+        .map(|location| {
+          if location.start_line == location.end_line {
+            location.start_line.to_string()
+          } else {
+            format!("{}-{}", location.start_line, location.end_line)
+          }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+      let mut support = feature
+        .support
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(browser, version)| format!("{} {}", browser, version))
+        .collect::<Vec<String>>();
+      support.sort();
+
+      lines.push(format!(
+        "| `{}` | {} | {} | {} | [MDN]({}) |",
+        cell(feature.feat_type.key()),
+        standard,
+        ranges,
+        cell(&support.join(", ")),
+        feature.mdn_url
+      ));
+    }
+  }
+
+  lines.push(String::new());
+  lines.join("\n")
+}
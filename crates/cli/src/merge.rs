@@ -0,0 +1,88 @@
+use jsco::report::{merge_reports, Reports};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::targets::Target;
+
+/// The parts of a `--format json` document that merging reads back.
+#[derive(Debug, Deserialize)]
+struct JsonInput {
+  #[serde(default)]
+  targets: BTreeMap<String, Vec<String>>,
+  reports: Option<Reports>,
+  #[serde(default)]
+  truncated: bool,
+}
+
+/// One run's findings and the targets they were evaluated against.
+#[derive(Debug)]
+pub struct Document {
+  pub reports: Reports,
+  pub targets: Vec<Target>,
+  /// The run stopped at `--max-findings`, so `reports` is incomplete
+  pub truncated: bool,
+}
+
+/// Parses a document written by `--format json`. Documents grouped by
+/// feature don't keep per-file reports, so they're rejected.
+pub fn parse_document(data: &str) -> Result<Document, String> {
+  let input: JsonInput = serde_json::from_str(data).map_err(|e| e.to_string())?;
+  let reports = input.reports.ok_or_else(|| {
+    "no per-file reports; documents written with --group-by feature can't be merged".to_string()
+  })?;
+  let targets = input
+    .targets
+    .into_iter()
+    .flat_map(|(name, versions)| {
+      versions
+        .into_iter()
+        .map(move |version| Target::new(name.clone(), version))
+    })
+    .collect();
+  Ok(Document {
+    reports,
+    targets,
+    truncated: input.truncated,
+  })
+}
+
+/// Combines documents from separate runs, e.g. one per package of a
+/// monorepo: reports go through `merge_reports`, keeping the first one per
+/// path, and the targets are unioned. The merge is truncated if any run was.
+pub fn merge_documents(documents: Vec<Document>) -> Document {
+  let mut targets = BTreeSet::new();
+  let mut runs = Vec::new();
+  let mut truncated = false;
+  for document in documents {
+    truncated |= document.truncated;
+    targets.extend(
+      document
+        .targets
+        .iter()
+        .map(|target| (target.name().to_string(), target.version().to_string())),
+    );
+    runs.push(document.reports);
+  }
+  Document {
+    reports: merge_reports(runs),
+    targets: targets
+      .into_iter()
+      .map(|(name, version)| Target::new(name, version))
+      .collect(),
+    truncated,
+  }
+}
+
+/// File, finding and feature counts for a merged document.
+pub fn summary(reports: &Reports) -> Value {
+  let found_features = || reports.iter().flat_map(|report| &report.found_features);
+  let features = found_features()
+    .map(|feature| feature.feat_type.key())
+    .collect::<BTreeSet<&str>>();
+  json!({
+    "files": reports.len(),
+    "findings": found_features().count(),
+    "features": features,
+  })
+}
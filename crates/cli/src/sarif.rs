@@ -0,0 +1,97 @@
+use jsco::feature::Location;
+use jsco::report::Reports;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::severity::is_feature_supported;
+use crate::targets::Target;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// Longest source excerpt quoted in a result message.
+const MAX_SNIPPET_CHARS: usize = 60;
+
+/// SARIF columns count UTF-16 code units by default, while locations count
+/// bytes. Converts the 1-based byte column of `offset` on its line.
+fn utf16_column(source_code: &str, offset: usize, byte_col: usize) -> usize {
+  let line_start = offset.saturating_sub(byte_col.saturating_sub(1));
+  source_code
+    .get(line_start..offset)
+    .map_or(byte_col, |prefix| prefix.encode_utf16().count() + 1)
+}
+
+/// The start of a location's code, on one line and cut to a readable length.
+fn snippet(location: &Location) -> String {
+  let first_line = location.code.lines().next().unwrap_or_default();
+  let mut snippet = first_line
+    .chars()
+    .take(MAX_SNIPPET_CHARS)
+    .collect::<String>();
+  if snippet.len() < location.code.len() {
+    snippet.push('…');
+  }
+  snippet
+}
+
+/// Renders the findings as a SARIF 2.1.0 log for code scanning uploads. Each
+/// location is one result; features the targets don't support are warnings,
+/// the rest, and informational ones, notes.
+pub fn render_sarif(reports: &Reports, targets: &[Target]) -> Value {
+  // Feature key -> MDN URL, ordered so rule indices are stable
+  let mut rules: BTreeMap<&str, &str> = BTreeMap::new();
+  for feature in reports.iter().flat_map(|report| &report.found_features) {
+    rules.insert(feature.feat_type.key(), &feature.mdn_url);
+  }
+  let rule_index = |key: &str| rules.keys().position(|rule| *rule == key);
+
+  let mut results = Vec::new();
+  for report in reports {
+    for feature in &report.found_features {
+      let level = if feature.feat_type.is_informational() || is_feature_supported(feature, targets)
+      {
+        "note"
+      } else {
+        "warning"
+      };
+      let key = feature.feat_type.key();
+      for location in &feature.locations {
+        results.push(json!({
+          "ruleId": key,
+          "ruleIndex": rule_index(key),
+          "level": level,
+          "message": { "text": format!("{:?} used in `{}`", feature.feat_type, snippet(location)) },
+          "locations": [{
+            "physicalLocation": {
+              "artifactLocation": { "uri": report.path },
+              "region": {
+                "startLine": location.start_line,
+                "startColumn": utf16_column(&report.source_code, location.start, location.start_col),
+                "endLine": location.end_line,
+                "endColumn": utf16_column(&report.source_code, location.end, location.end_col),
+              },
+            },
+          }],
+        }));
+      }
+    }
+  }
+
+  let rule_entries = rules
+    .iter()
+    .map(|(key, mdn_url)| json!({ "id": key, "helpUri": mdn_url }))
+    .collect::<Vec<Value>>();
+  json!({
+    "$schema": SARIF_SCHEMA,
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": "jsco",
+          "version": env!("CARGO_PKG_VERSION"),
+          "rules": rule_entries,
+        },
+      },
+      "columnKind": "utf16CodeUnits",
+      "results": results,
+    }],
+  })
+}
@@ -0,0 +1,52 @@
+use jsco::feature::{FeatureReport, JsFeature, Severity};
+use std::collections::HashMap;
+
+use crate::is_supported;
+use crate::targets::Target;
+
+/// Per-feature severity overrides from `--severity FEATURE=LEVEL`.
+pub type SeverityMap = HashMap<JsFeature, Severity>;
+
+/// Parses `FEATURE=LEVEL`. Besides built-in names, any BCD key is accepted
+/// so custom detectors can be targeted too.
+pub fn parse_severity_rule(rule: &str) -> Result<(JsFeature, Severity), String> {
+  let (name, level) = rule
+    .split_once('=')
+    .ok_or_else(|| format!("expected FEATURE=LEVEL, got: {}", rule))?;
+  let name = name.trim();
+  let feature = JsFeature::from_name(name)
+    .or_else(|| name.contains('.').then(|| JsFeature::from_key(name)))
+    .ok_or_else(|| format!("unknown feature: {}", name))?;
+  Ok((feature, level.trim().parse()?))
+}
+
+/// Whether every browser's required version is met by the targets.
+pub fn is_feature_supported(feature: &FeatureReport, targets: &[Target]) -> bool {
+  feature
+    .support
+    .lock()
+    .unwrap()
+    .iter()
+    .all(|(browser, version)| is_supported(browser, version, targets))
+}
+
+/// The severity of a finding: an explicit override if configured, `Error` when
+/// the targets don't support it, `Warn` when BCD marks it deprecated and
+/// `Info` otherwise, e.g. for experimental features. Informational features
+/// are never unsupported.
+pub fn severity_for(
+  feature: &FeatureReport,
+  overrides: &SeverityMap,
+  targets: &[Target],
+) -> Severity {
+  if let Some(severity) = overrides.get(&feature.feat_type) {
+    return *severity;
+  }
+  if !feature.feat_type.is_informational() && !is_feature_supported(feature, targets) {
+    Severity::Error
+  } else if feature.deprecated {
+    Severity::Warn
+  } else {
+    Severity::Info
+  }
+}
@@ -0,0 +1,166 @@
+use browserslist::{execute, resolve, Distrib, Opts};
+use clap::ValueEnum;
+use jsco::feature::JsFeature;
+use std::{collections::BTreeMap, fmt, fs};
+
+/// Canned target sets for users who don't want to write browserslist queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+  /// Last 2 versions of every maintained browser
+  Modern,
+  /// IE 11 and old Safari/Chrome/Firefox releases
+  Legacy,
+  /// Only the latest release of each major browser
+  Esnext,
+}
+
+impl Preset {
+  pub fn queries(&self) -> &'static [&'static str] {
+    match self {
+      Preset::Modern => &["last 2 versions", "not dead"],
+      Preset::Legacy => &[
+        "ie 11",
+        "safari >= 10",
+        "ios_saf >= 10",
+        "chrome >= 49",
+        "firefox >= 52",
+        "edge >= 15",
+      ],
+      Preset::Esnext => &[
+        "last 1 chrome version",
+        "last 1 firefox version",
+        "last 1 safari version",
+        "last 1 edge version",
+      ],
+    }
+  }
+
+  /// Opt-in detectors that only matter for this preset's targets.
+  pub fn enabled_features(&self) -> &'static [JsFeature] {
+    match self {
+      Preset::Legacy => &[JsFeature::ArrayIsArray, JsFeature::ObjectKeys],
+      Preset::Modern | Preset::Esnext => &[],
+    }
+  }
+}
+
+/// Where the target browsers came from, for labeling reports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TargetSource {
+  /// The ambient browserslist config
+  #[default]
+  Browserslistrc,
+  Preset(Preset),
+  /// A `--browsers` query
+  Query(String),
+  /// A `--targets` file
+  File(String),
+}
+
+impl fmt::Display for TargetSource {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TargetSource::Browserslistrc => write!(f, "from .browserslistrc"),
+      TargetSource::Preset(preset) => {
+        write!(f, "{} preset", format!("{:?}", preset).to_lowercase())
+      }
+      TargetSource::Query(query) => write!(f, "query \"{}\"", query),
+      TargetSource::File(path) => write!(f, "from {}", path),
+    }
+  }
+}
+
+/// Browser names a targets file may use: the ones BCD support is checked
+/// against, under their browserslist names.
+const TARGET_BROWSERS: [&str; 6] = ["chrome", "and_chr", "edge", "firefox", "safari", "ios_saf"];
+
+/// One target browser release, e.g. `safari 15.4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+  name: String,
+  version: String,
+}
+
+impl Target {
+  pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      version: version.into(),
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn version(&self) -> &str {
+    &self.version
+  }
+}
+
+impl From<Distrib> for Target {
+  fn from(distrib: Distrib) -> Self {
+    Target::new(distrib.name(), distrib.version())
+  }
+}
+
+fn from_distribs(distribs: Vec<Distrib>) -> Vec<Target> {
+  distribs.into_iter().map(Target::from).collect()
+}
+
+/// Resolves the target browsers, from a preset when given and from the
+/// ambient browserslist config otherwise.
+pub fn resolve_targets(preset: Option<Preset>) -> Vec<Target> {
+  let opts = Opts::default();
+  let distribs = match preset {
+    Some(preset) => resolve(preset.queries(), &opts).unwrap_or_default(),
+    None => execute(&opts).unwrap_or_default(),
+  };
+  from_distribs(distribs)
+}
+
+/// Resolves a browserslist query given on the command line, e.g.
+/// `"last 2 versions, not dead"`.
+pub fn resolve_query(query: &str) -> Result<Vec<Target>, String> {
+  resolve(&[query], &Opts::default())
+    .map(from_distribs)
+    .map_err(|e| e.to_string())
+}
+
+/// Loads exact target versions from a JSON file such as
+/// `{ "chrome": "91", "safari": "15.4" }`. The versions are used as written,
+/// without going through browserslist.
+pub fn load_targets_file(path: &str) -> Result<Vec<Target>, String> {
+  let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+  parse_targets(&data)
+}
+
+/// Parses the contents of a targets file; see `load_targets_file`. A `node`
+/// entry is skipped with a warning, since only browser support is checked.
+pub fn parse_targets(data: &str) -> Result<Vec<Target>, String> {
+  let versions: BTreeMap<String, String> = serde_json::from_str(data).map_err(|e| e.to_string())?;
+  versions
+    .into_iter()
+    .filter(|(browser, version)| {
+      let is_node = browser.eq_ignore_ascii_case("node");
+      if is_node {
+        eprintln!(
+          "Ignoring target node {}: only browsers are checked",
+          version
+        );
+      }
+      !is_node
+    })
+    .map(|(browser, version)| {
+      let name = browser.to_lowercase();
+      if !TARGET_BROWSERS.contains(&name.as_str()) {
+        return Err(format!(
+          "unsupported target {:?}; expected one of {}",
+          browser,
+          TARGET_BROWSERS.join(", ")
+        ));
+      }
+      Ok(Target::new(name, version))
+    })
+    .collect()
+}
@@ -0,0 +1,51 @@
+use jsco::report::Reports;
+
+/// Compact terminal summary for `--format console`: each file with its
+/// features, where they occur and the first supporting browser versions.
+pub fn render_text(reports: &Reports) -> String {
+  let mut lines = Vec::new();
+  for report in reports
+    .iter()
+    .filter(|report| !report.found_features.is_empty())
+  {
+    lines.push(report.path.clone());
+    for feature in &report.found_features {
+      let ranges = feature
+        .locations
+        .iter()
+        .map(|location| {
+          if location.start_line == location.end_line {
+            format!("L{}:{}", location.start_line, location.start_col)
+          } else {
+            format!("L{}-{}", location.start_line, location.end_line)
+          }
+        })
+        .collect::<Vec<String>>();
+
+      let mut support = feature
+        .support
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(browser, version)| format!("{} {}", browser, version))
+        .collect::<Vec<String>>();
+      support.sort();
+      let support = if support.is_empty() {
+        "no support data".to_string()
+      } else {
+        support.join(", ")
+      };
+
+      lines.push(format!(
+        "  {}  {}  ({})",
+        feature.feat_type.key(),
+        ranges.join(" "),
+        support
+      ));
+    }
+  }
+  if lines.is_empty() {
+    lines.push("No compatibility-relevant features found".to_string());
+  }
+  lines.join("\n")
+}
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use jsco_cli::parse_bcd_age;
+
+#[test]
+fn bcd_age_is_parsed_in_days() {
+  assert_eq!(
+    parse_bcd_age("2"),
+    Ok(Duration::from_secs(2 * 24 * 60 * 60))
+  );
+}
+
+#[test]
+fn bcd_age_rejects_overflowing_day_counts() {
+  let err = parse_bcd_age(&u64::MAX.to_string()).unwrap_err();
+  assert!(err.contains("too long"), "{}", err);
+  assert!(parse_bcd_age("soon").is_err());
+}
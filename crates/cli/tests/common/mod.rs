@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use jsco::report::Report;
+use jsco::Options;
+
+/// Loads BCD from the core crate's trimmed snapshot, never the network.
+pub async fn setup() {
+  jsco::cache::set_dir(std::env::temp_dir().join(format!("jsco-cli-test-{}", std::process::id())));
+  jsco::bcd::use_bcd_file(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../core/tests/fixtures/bcd.json"),
+  );
+  jsco::download::set_offline(true);
+  jsco::bcd::load()
+    .await
+    .expect("fixture BCD data should load");
+}
+
+/// Analyzes `code` as if read from `path`, with `options`.
+#[allow(dead_code)]
+pub async fn check(path: &str, code: &str, options: &Options) -> Report {
+  setup().await;
+  let mut report = Report::new(path.to_string(), code.to_string());
+  report.check_feature_with(options);
+  report.prepare_output();
+  report
+}
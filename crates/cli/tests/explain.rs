@@ -0,0 +1,21 @@
+mod common;
+
+use jsco::feature::JsFeature;
+use jsco_cli::explain::explain;
+
+#[tokio::test]
+async fn explanations_list_support_versions() {
+  common::setup().await;
+  let text = explain(JsFeature::OptionalChaining);
+
+  assert!(
+    text.starts_with("OptionalChaining (javascript.operators.optional_chaining)"),
+    "{}",
+    text
+  );
+  assert!(text.contains("Standard: ES2020"), "{}", text);
+  assert!(text.contains("Optional_chaining"), "{}", text);
+  for line in ["chrome   80", "firefox  74", "safari   13.1"] {
+    assert!(text.contains(line), "{}", text);
+  }
+}
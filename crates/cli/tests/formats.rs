@@ -0,0 +1,45 @@
+mod common;
+
+use jsco::feature::JsFeature;
+use jsco::Options;
+use jsco_cli::targets::Target;
+use jsco_cli::{detected_keys, render_junit, render_sarif};
+
+#[tokio::test]
+async fn informational_findings_are_notes_and_passes() {
+  let mut options = Options::default();
+  options.enabled.insert(JsFeature::ArraySortStability);
+  let report = common::check("input.js", "arr.sort(cmp);\n", &options).await;
+  let reports = vec![report];
+  // Older than every browser's stable sort
+  let targets = [Target::new("chrome", "60")];
+
+  let sarif = render_sarif(&reports, &targets);
+  let levels = sarif["runs"][0]["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|result| result["level"].as_str().unwrap())
+    .collect::<Vec<_>>();
+  assert_eq!(levels, ["note"]);
+
+  let junit = render_junit(&reports, &targets);
+  assert!(junit.contains("failures=\"0\""), "{}", junit);
+}
+
+#[tokio::test]
+async fn keys_are_sorted_and_deduplicated_across_files() {
+  let options = Options::default();
+  let reports = vec![
+    common::check("a.js", "a?.b;\nx ?? y;\n", &options).await,
+    common::check("b.js", "c?.d;\n", &options).await,
+  ];
+
+  assert_eq!(
+    detected_keys(&reports).into_iter().collect::<Vec<_>>(),
+    [
+      "javascript.operators.nullish_coalescing",
+      "javascript.operators.optional_chaining"
+    ]
+  );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use jsco::Options;
+use jsco_cli::render_html;
+use jsco_cli::targets::{Preset, Target, TargetSource};
+
+#[test]
+fn targets_panel_names_where_the_targets_came_from() {
+  let targets = [Target::new("chrome", "100")];
+  let query = TargetSource::Query("chrome 100".to_string());
+  let html = render_html(&Vec::new(), &targets, &query, false, false);
+  assert!(html.contains("chrome 100"));
+  assert!(!html.contains(".browserslistrc"));
+
+  let html = render_html(
+    &Vec::new(),
+    &targets,
+    &TargetSource::Preset(Preset::Legacy),
+    false,
+    false,
+  );
+  assert!(html.contains("(legacy preset)"));
+
+  let html = render_html(
+    &Vec::new(),
+    &targets,
+    &TargetSource::Browserslistrc,
+    false,
+    false,
+  );
+  assert!(html.contains("(from .browserslistrc)"));
+}
+
+#[tokio::test]
+async fn findings_render_as_feature_cards_under_the_title() {
+  let report = common::check("input.js", "a?.b;\n", &Options::default()).await;
+  let targets = [Target::new("chrome", "100")];
+  let html = render_html(
+    &vec![report],
+    &targets,
+    &TargetSource::Browserslistrc,
+    false,
+    false,
+  );
+
+  assert!(html.contains("<title>JavaScript Compatibility Report</title>"));
+  assert!(html.contains("class=\"feature-card "));
+  assert!(html.contains("OptionalChaining"));
+}
+
+#[tokio::test]
+async fn concise_output_leaves_out_the_targets_panel() {
+  let report = common::check("input.js", "a?.b;\n", &Options::default()).await;
+  let reports = vec![report];
+  let targets = [Target::new("chrome", "100")];
+
+  let full = render_html(
+    &reports,
+    &targets,
+    &TargetSource::Browserslistrc,
+    false,
+    false,
+  );
+  assert!(full.contains("Target Browsers"));
+  let concise = render_html(
+    &reports,
+    &targets,
+    &TargetSource::Browserslistrc,
+    true,
+    false,
+  );
+  assert!(!concise.contains("Target Browsers"));
+  assert!(concise.contains("OptionalChaining"));
+}
+
+#[tokio::test]
+async fn the_chart_has_one_segment_per_es_year() {
+  let source = "const { a } = obj;\nconst b = 2 ** 3;\nasync function c() {\n  await d();\n}\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  let years = report
+    .found_features
+    .iter()
+    .filter_map(|feature| feature.feat_type.es_year())
+    .collect::<std::collections::BTreeSet<_>>();
+  assert!(years.len() >= 3, "{:?}", years);
+
+  let targets = [Target::new("chrome", "100")];
+  let html = render_html(
+    &vec![report],
+    &targets,
+    &TargetSource::Browserslistrc,
+    false,
+    false,
+  );
+  assert_eq!(html.matches("class=\"es-year\"").count(), years.len());
+  for year in [2015, 2016, 2017] {
+    assert!(
+      html.contains(&format!("data-year=\"{}\"", year)),
+      "{}",
+      year
+    );
+  }
+}
@@ -0,0 +1,46 @@
+use jsco_cli::is_supported;
+use jsco_cli::targets::Target;
+
+#[test]
+fn minor_versions_are_compared() {
+  let targets = [Target::new("safari", "15.0")];
+  assert!(!is_supported("safari", "15.4", &targets));
+  assert!(is_supported("safari", "15", &targets));
+  assert!(is_supported("safari", "14.1", &targets));
+}
+
+#[test]
+fn patch_versions_are_compared() {
+  let targets = [Target::new("safari", "13.1")];
+  assert!(!is_supported("safari", "13.1.2", &targets));
+  assert!(is_supported("safari", "13.1.0", &targets));
+}
+
+#[test]
+fn upper_bound_versions_use_the_bound() {
+  let targets = [Target::new("edge", "79")];
+  assert!(is_supported("edge", "≤79", &targets));
+  assert!(!is_supported("edge", "≤80", &targets));
+}
+
+#[test]
+fn target_ranges_use_the_lower_bound() {
+  let targets = [Target::new("ios_saf", "15.2-15.3")];
+  assert!(is_supported("safari", "15.2", &targets));
+  assert!(!is_supported("safari", "15.3", &targets));
+}
+
+#[test]
+fn android_chrome_counts_as_chrome() {
+  let targets = [Target::new("and_chr", "90")];
+  assert!(is_supported("chrome", "90", &targets));
+  assert!(!is_supported("chrome", "91", &targets));
+}
+
+#[test]
+fn sentinels() {
+  let targets = [Target::new("chrome", "120")];
+  assert!(is_supported("chrome", "true", &targets));
+  assert!(!is_supported("chrome", "false", &targets));
+  assert!(!is_supported("chrome", "preview", &targets));
+}
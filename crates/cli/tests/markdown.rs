@@ -0,0 +1,25 @@
+mod common;
+
+use jsco::Options;
+use jsco_cli::render_markdown;
+
+#[tokio::test]
+async fn markdown_has_totals_and_a_table_per_file() {
+  let options = Options::default();
+  let reports = vec![
+    common::check("a.js", "a?.b;\nx ?? y;\n", &options).await,
+    common::check("clean.js", "var c = 1;\n", &options).await,
+  ];
+  let markdown = render_markdown(&reports);
+
+  for line in [
+    "- Total files processed: 2",
+    "- Files with features: 1",
+    "- Total features found: 2",
+    "## `a.js`",
+    "| `javascript.operators.optional_chaining` | ES2020 | 1 | chrome 80, edge 80, firefox 74, safari 13.1 | [MDN](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Operators/Optional_chaining) |",
+  ] {
+    assert!(markdown.contains(line), "{}\n{}", line, markdown);
+  }
+  assert!(!markdown.contains("clean.js"), "{}", markdown);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use jsco::report::min_required_versions;
+use jsco::Options;
+use jsco_cli::merge::{merge_documents, parse_document, summary};
+use jsco_cli::targets::Target;
+use serde_json::json;
+
+async fn document(path: &str, code: &str, browser: &str, version: &str) -> String {
+  let report = common::check(path, code, &Options::default()).await;
+  let mut targets = serde_json::Map::new();
+  targets.insert(browser.to_string(), json!([version]));
+  json!({ "targets": targets, "reports": [report] }).to_string()
+}
+
+#[tokio::test]
+async fn merging_combines_reports_and_targets() {
+  let first = parse_document(&document("a.js", "a?.b;\n", "chrome", "79").await).unwrap();
+  let second = parse_document(&document("b.js", "a ?? b;\n", "safari", "13").await).unwrap();
+  let again = parse_document(&document("a.js", "a?.b;\n", "chrome", "79").await).unwrap();
+
+  let merged = merge_documents(vec![first, second, again]);
+  assert_eq!(merged.reports.len(), 2);
+  assert_eq!(
+    merged.targets,
+    [Target::new("chrome", "79"), Target::new("safari", "13")]
+  );
+
+  let totals = summary(&merged.reports);
+  assert_eq!(totals["files"], 2);
+  assert_eq!(totals["findings"], 2);
+  assert_eq!(
+    totals["features"],
+    json!([
+      "javascript.operators.nullish_coalescing",
+      "javascript.operators.optional_chaining"
+    ])
+  );
+  let required = min_required_versions(&merged.reports);
+  assert_eq!(required.get("safari").map(String::as_str), Some("13.1"));
+}
+
+#[test]
+fn documents_grouped_by_feature_are_rejected() {
+  let grouped = json!({ "targets": {}, "features": [] }).to_string();
+  let err = parse_document(&grouped).unwrap_err();
+  assert!(err.contains("--group-by feature"), "{}", err);
+}
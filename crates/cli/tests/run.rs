@@ -0,0 +1,279 @@
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use jsco_cli::group_targets;
+use jsco_cli::targets::resolve_query;
+
+fn args(rest: &[&str]) -> Vec<String> {
+  std::iter::once("jsco")
+    .chain(rest.iter().copied())
+    .map(String::from)
+    .collect()
+}
+
+/// Writes `files` into a fresh scratch directory named after `name`.
+fn scratch_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("jsco-{}-{}", name, std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  for (file, code) in files {
+    std::fs::write(dir.join(file), code).unwrap();
+  }
+  dir
+}
+
+/// Reads and removes a JSON report written relative to the working
+/// directory.
+fn take_json(output: &str) -> serde_json::Value {
+  let document = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+  let _ = std::fs::remove_file(output);
+  document
+}
+
+/// Serves `statuses` in turn on a local port (repeating the last one) and
+/// records every request body. Returns the URL to post to.
+fn mock_server(statuses: &[u16]) -> (String, Arc<Mutex<Vec<String>>>) {
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  let url = format!("http://{}/reports", listener.local_addr().unwrap());
+  let bodies = Arc::new(Mutex::new(Vec::new()));
+  let received = bodies.clone();
+  let statuses = statuses.to_vec();
+  std::thread::spawn(move || {
+    for (index, stream) in listener.incoming().enumerate() {
+      let mut reader = BufReader::new(stream.unwrap());
+      let mut length = 0;
+      loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+          break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+          if name.eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().unwrap();
+          }
+        }
+      }
+      let mut body = vec![0; length];
+      reader.read_exact(&mut body).unwrap();
+      received
+        .lock()
+        .unwrap()
+        .push(String::from_utf8(body).unwrap());
+      let status = statuses[index.min(statuses.len() - 1)];
+      write!(
+        reader.into_inner(),
+        "HTTP/1.1 {} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+      )
+      .unwrap();
+    }
+  });
+  (url, bodies)
+}
+
+#[tokio::test]
+async fn unreadable_inputs_fail_the_run() {
+  common::setup().await;
+  let code = jsco_cli::run(args(&[
+    "no-such-dir/typo.js",
+    "--quiet",
+    "--browsers",
+    "chrome 100",
+  ]))
+  .await;
+  assert_eq!(code, 1);
+}
+
+#[tokio::test]
+async fn json_output_lists_the_resolved_targets() {
+  common::setup().await;
+  let input = scratch_dir("targets", &[("input.js", "a?.b;\n")]).join("input.js");
+
+  let query = "chrome 100, safari 15";
+  let code = jsco_cli::run(args(&[
+    input.to_str().unwrap(),
+    "--quiet",
+    "--format",
+    "json",
+    "--browsers",
+    query,
+    "--timestamp",
+    "453",
+  ]))
+  .await;
+  assert_eq!(code, 0);
+
+  // Named after the timestamp
+  let document = take_json("jsco-output/report_19700101_000733.json");
+  let expected = group_targets(&resolve_query(query).unwrap());
+  assert_eq!(
+    document["targets"],
+    serde_json::to_value(&expected).unwrap()
+  );
+  assert_eq!(document["targets"]["safari"], serde_json::json!(["15"]));
+}
+
+#[tokio::test]
+async fn several_formats_are_written_in_one_run() {
+  common::setup().await;
+  let input = scratch_dir("formats", &[("input.js", "a?.b;\n")]).join("input.js");
+
+  let code = jsco_cli::run(args(&[
+    input.to_str().unwrap(),
+    "--quiet",
+    "--format",
+    "html,json",
+    "--print-output-path",
+    "--browsers",
+    "chrome 100",
+    "--timestamp",
+    "455",
+  ]))
+  .await;
+  assert_eq!(code, 0);
+
+  for output in [
+    "jsco-output/report_19700101_000735.html",
+    "jsco-output/report_19700101_000735.json",
+  ] {
+    assert!(std::path::Path::new(output).exists(), "{}", output);
+    let _ = std::fs::remove_file(output);
+  }
+}
+
+#[tokio::test]
+async fn clean_files_are_left_out_of_json_unless_asked_for() {
+  common::setup().await;
+  let dir = scratch_dir(
+    "clean",
+    &[("found.js", "a?.b;\n"), ("clean.js", "var a = 1;\n")],
+  );
+  let pattern = format!("{}/*.js", dir.display());
+  let paths = |document: &serde_json::Value| {
+    document["reports"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|report| {
+        let path = report["path"].as_str().unwrap();
+        path.rsplit('/').next().unwrap().to_string()
+      })
+      .collect::<Vec<_>>()
+  };
+  let base = [
+    pattern.as_str(),
+    "--quiet",
+    "--format",
+    "json",
+    "--browsers",
+    "chrome 100",
+  ];
+
+  let code = jsco_cli::run(args(&[&base[..], &["--timestamp", "478"][..]].concat())).await;
+  assert_eq!(code, 0);
+  let document = take_json("jsco-output/report_19700101_000758.json");
+  assert_eq!(paths(&document), ["found.js"]);
+
+  let code = jsco_cli::run(args(
+    &[&base[..], &["--timestamp", "1478", "--include-clean"][..]].concat(),
+  ))
+  .await;
+  assert_eq!(code, 0);
+  let document = take_json("jsco-output/report_19700101_002438.json");
+  assert_eq!(paths(&document), ["clean.js", "found.js"]);
+}
+
+#[tokio::test]
+async fn reports_are_posted_as_json_after_server_errors() {
+  common::setup().await;
+  let input = scratch_dir("post", &[("input.js", "a?.b;\n")]).join("input.js");
+  let (url, bodies) = mock_server(&[503, 200]);
+
+  let code = jsco_cli::run(args(&[
+    input.to_str().unwrap(),
+    "--quiet",
+    "--format",
+    "json",
+    "--browsers",
+    "chrome 100",
+    "--timestamp",
+    "468",
+    "--post-to",
+    &url,
+  ]))
+  .await;
+  assert_eq!(code, 0);
+  let _ = take_json("jsco-output/report_19700101_000748.json");
+
+  // Retried once after the 503
+  let bodies = bodies.lock().unwrap();
+  assert_eq!(bodies.len(), 2);
+  let document: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+  assert_eq!(document["targets"]["chrome"], serde_json::json!(["100"]));
+  assert_eq!(document["reports"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+  common::setup().await;
+  let input = scratch_dir("post-rejected", &[("input.js", "a?.b;\n")]).join("input.js");
+  let (url, bodies) = mock_server(&[400]);
+
+  let code = jsco_cli::run(args(&[
+    input.to_str().unwrap(),
+    "--quiet",
+    "--format",
+    "json",
+    "--browsers",
+    "chrome 100",
+    "--timestamp",
+    "1468",
+    "--post-to",
+    &url,
+  ]))
+  .await;
+  assert_eq!(code, 1);
+  let _ = take_json("jsco-output/report_19700101_002428.json");
+  assert_eq!(bodies.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn stdout_only_carries_the_output_path_line() {
+  let dir = scratch_dir("output-path", &[("input.js", "a?.b;\n")]);
+  let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../core/tests/fixtures/bcd.json");
+
+  let output = std::process::Command::new(env!("CARGO_BIN_EXE_jsco"))
+    .current_dir(&dir)
+    .args(["input.js", "--format", "json", "--print-output-path"])
+    .args([
+      "--browsers",
+      "chrome 100",
+      "--timestamp",
+      "480",
+      "--offline",
+    ])
+    .arg("--bcd-file")
+    .arg(&fixture)
+    .arg("--cache-dir")
+    .arg(dir.join("cache"))
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+
+  // Status lines such as "Report saved to:" go to stderr
+  let path = "jsco-output/report_19700101_000800.json";
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  let lines = stdout.lines().collect::<Vec<_>>();
+  assert_eq!(lines.len(), 1, "{}", stdout);
+  assert_eq!(
+    serde_json::from_str::<serde_json::Value>(lines[0]).unwrap(),
+    serde_json::json!({ "format": "json", "path": path })
+  );
+  assert!(String::from_utf8_lossy(&output.stderr).contains("Report saved to:"));
+  assert!(dir.join(path).exists());
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use jsco::Options;
+use jsco_cli::render_sarif;
+use jsco_cli::targets::Target;
+
+async fn sarif_results(source: &str) -> Vec<serde_json::Value> {
+  let report = common::check("input.js", source, &Options::default()).await;
+  let sarif = render_sarif(&vec![report], &[Target::new("chrome", "100")]);
+  sarif["runs"][0]["results"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn columns_count_utf16_code_units() {
+  // "é" is two bytes but one UTF-16 unit, "😀" four bytes but two units
+  let results = sarif_results("const s = \"é😀\"; a?.b;\n").await;
+  let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+  assert_eq!(region["startColumn"], 18);
+  assert_eq!(region["endColumn"], 22);
+}
+
+#[tokio::test]
+async fn long_code_is_truncated_in_messages() {
+  let chain = format!("a?.{}", "b.".repeat(100) + "c");
+  let results = sarif_results(&format!("{};\n", chain)).await;
+  let message = results[0]["message"]["text"].as_str().unwrap();
+  assert!(message.len() < 100, "{}", message);
+  assert!(message.contains('…'), "{}", message);
+}
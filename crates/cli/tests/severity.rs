@@ -0,0 +1,58 @@
+mod common;
+
+use jsco::feature::{FeatureReport, JsFeature, Severity};
+use jsco::Options;
+use jsco_cli::severity::{severity_for, SeverityMap};
+use jsco_cli::targets::Target;
+
+async fn feature(code: &str, feature: JsFeature) -> FeatureReport {
+  let mut options = Options::default();
+  options.enabled.insert(feature);
+  let report = common::check("input.js", code, &options).await;
+  report
+    .found_features
+    .into_iter()
+    .find(|found| found.feat_type == feature)
+    .expect("feature is detected")
+}
+
+#[tokio::test]
+async fn unsupported_features_are_errors() {
+  let chaining = feature("a?.b;\n", JsFeature::OptionalChaining).await;
+  let overrides = SeverityMap::new();
+
+  let old = [Target::new("chrome", "79")];
+  assert_eq!(severity_for(&chaining, &overrides, &old), Severity::Error);
+  let new = [Target::new("chrome", "80")];
+  assert_eq!(severity_for(&chaining, &overrides, &new), Severity::Info);
+}
+
+#[tokio::test]
+async fn deprecated_features_are_warnings() {
+  let escape = feature("escape(text);\n", JsFeature::Escape).await;
+  assert!(escape.deprecated);
+  let targets = [Target::new("chrome", "100")];
+  assert_eq!(
+    severity_for(&escape, &SeverityMap::new(), &targets),
+    Severity::Warn
+  );
+}
+
+#[tokio::test]
+async fn overrides_win() {
+  let chaining = feature("a?.b;\n", JsFeature::OptionalChaining).await;
+  let overrides = SeverityMap::from([(JsFeature::OptionalChaining, Severity::Off)]);
+  let old = [Target::new("chrome", "79")];
+  assert_eq!(severity_for(&chaining, &overrides, &old), Severity::Off);
+}
+
+#[test]
+fn severity_rules_accept_custom_keys() {
+  let key = "javascript.builtins.Array.reduceRight";
+  let (feature, severity) =
+    jsco_cli::severity::parse_severity_rule(&format!("{}=error", key)).unwrap();
+  assert_eq!(feature, jsco::CustomMethod::new("reduceRight", key).feature);
+  assert_eq!(severity, Severity::Error);
+
+  assert!(jsco_cli::severity::parse_severity_rule("no_such_feature=error").is_err());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use jsco::feature::JsFeature;
+use jsco::Options;
+use jsco_cli::targets::{load_targets_file, parse_targets, resolve_targets, Preset, Target};
+use jsco_cli::{is_supported, unsupported_by_feature};
+
+#[test]
+fn targets_file_versions_are_used_as_written() {
+  let targets = parse_targets(r#"{ "chrome": "91", "safari": "15.4" }"#).unwrap();
+  assert_eq!(
+    targets,
+    [Target::new("chrome", "91"), Target::new("safari", "15.4")]
+  );
+
+  assert!(is_supported("chrome", "91", &targets));
+  assert!(!is_supported("chrome", "92", &targets));
+  assert!(is_supported("safari", "15.4", &targets));
+  assert!(!is_supported("safari", "16", &targets));
+}
+
+#[test]
+fn targets_file_skips_node() {
+  let dir = std::env::temp_dir().join(format!("jsco-targets-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let path = dir.join("targets.json");
+  std::fs::write(
+    &path,
+    r#"{ "chrome": "91", "safari": "15.4", "node": "18" }"#,
+  )
+  .unwrap();
+
+  let targets = load_targets_file(path.to_str().unwrap()).unwrap();
+  assert_eq!(
+    targets,
+    [Target::new("chrome", "91"), Target::new("safari", "15.4")]
+  );
+  assert!(is_supported("chrome", "91", &targets));
+  assert!(!is_supported("chrome", "92", &targets));
+  assert!(is_supported("safari", "15.4", &targets));
+  assert!(!is_supported("safari", "16", &targets));
+}
+
+#[test]
+fn targets_file_rejects_unknown_browsers() {
+  let err = parse_targets(r#"{ "chrome": "91", "opera": "80" }"#).unwrap_err();
+  assert!(err.contains("\"opera\""), "{}", err);
+}
+
+/// The features found in `source` with `preset`'s opt-ins, and those its
+/// targets don't support.
+async fn check_with_preset(
+  source: &str,
+  preset: Preset,
+) -> (Vec<JsFeature>, BTreeMap<String, Vec<String>>) {
+  let mut options = Options::default();
+  options
+    .enabled
+    .extend(preset.enabled_features().iter().copied());
+  let report = common::check("input.js", source, &options).await;
+  let detected = report
+    .found_features
+    .iter()
+    .map(|feature| feature.feat_type)
+    .collect();
+  let unsupported = unsupported_by_feature(&vec![report], &resolve_targets(Some(preset)));
+  (detected, unsupported)
+}
+
+#[tokio::test]
+async fn legacy_flags_what_esnext_lets_through() {
+  let source = "if (Array.isArray(items)) {\n  use(items[0]?.id);\n}\n";
+  let (legacy_features, legacy) = check_with_preset(source, Preset::Legacy).await;
+  let (esnext_features, esnext) = check_with_preset(source, Preset::Esnext).await;
+
+  // Only the legacy preset opts into ES5 builtins
+  assert!(legacy_features.contains(&JsFeature::ArrayIsArray));
+  assert!(!esnext_features.contains(&JsFeature::ArrayIsArray));
+  assert!(legacy.contains_key(JsFeature::OptionalChaining.key()));
+  assert!(!esnext.contains_key(JsFeature::OptionalChaining.key()));
+}
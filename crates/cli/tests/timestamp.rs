@@ -0,0 +1,40 @@
+mod common;
+
+// Sets SOURCE_DATE_EPOCH for the whole process, so it lives in its own
+// test binary
+#[tokio::test]
+async fn output_names_follow_the_fixed_timestamp() {
+  common::setup().await;
+  let dir = std::env::temp_dir().join(format!("jsco-timestamp-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let input = dir.join("input.js");
+  std::fs::write(&input, "a?.b;\n").unwrap();
+  let args = |extra: &[&str]| {
+    [
+      "jsco",
+      input.to_str().unwrap(),
+      "--quiet",
+      "--format",
+      "json",
+    ]
+    .iter()
+    .chain(["--browsers", "chrome 100"].iter())
+    .chain(extra)
+    .map(|arg| arg.to_string())
+    .collect::<Vec<_>>()
+  };
+
+  std::env::set_var("SOURCE_DATE_EPOCH", "486");
+  assert_eq!(jsco_cli::run(args(&[])).await, 0);
+  let output = "jsco-output/report_19700101_000806.json";
+  let document: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+  let _ = std::fs::remove_file(output);
+  assert_eq!(document["generated_at"], "1970-01-01T00:08:06+00:00");
+
+  // --timestamp wins over the environment
+  assert_eq!(jsco_cli::run(args(&["--timestamp", "1486"])).await, 0);
+  let output = "jsco-output/report_19700101_002446.json";
+  assert!(std::path::Path::new(output).exists());
+  let _ = std::fs::remove_file(output);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use jsco::feature::JsFeature;
+use jsco::Options;
+use jsco_cli::targets::Target;
+use jsco_cli::unsupported_by_feature;
+
+#[tokio::test]
+async fn decorators_block_every_target() {
+  let report = common::check("input.js", "@dec class A {}\n", &Options::default()).await;
+  let decorator = report
+    .found_features
+    .iter()
+    .find(|feature| feature.feat_type == JsFeature::Decorator)
+    .expect("decorator is reported");
+  for browser in ["chrome", "firefox", "safari", "edge"] {
+    assert_eq!(
+      decorator
+        .support
+        .lock()
+        .unwrap()
+        .get(browser)
+        .map(String::as_str),
+      Some("false"),
+      "{}",
+      browser
+    );
+  }
+
+  let targets = [Target::new("chrome", "130"), Target::new("safari", "18")];
+  let unsupported = unsupported_by_feature(&vec![report], &targets);
+  assert_eq!(
+    unsupported.get(JsFeature::Decorator.key()),
+    Some(&vec!["chrome 130".to_string(), "safari 18".to_string()])
+  );
+}
+
+#[tokio::test]
+async fn safari_blocks_optional_chaining_before_13_1() {
+  let report = common::check("input.js", "a?.b;\n", &Options::default()).await;
+  let targets = [
+    Target::new("chrome", "100"),
+    Target::new("firefox", "100"),
+    Target::new("safari", "13"),
+  ];
+  let unsupported = unsupported_by_feature(&vec![report], &targets);
+  assert_eq!(
+    unsupported.get(JsFeature::OptionalChaining.key()),
+    Some(&vec!["safari 13".to_string()])
+  );
+}
@@ -1,13 +1,24 @@
 use crate::{
-  download::download_with_progress,
+  cache,
+  download::{download_with_progress, is_offline},
+  error::JscoError,
   feature::{BrowserSupport, JsFeature, JsFeatureTrait},
+  progress,
 };
+use console::style;
 use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+use std::{
+  collections::HashMap,
+  fs,
+  path::PathBuf,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+use tokio::runtime::RuntimeFlavor;
 
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Default, Deserialize, Clone, Serialize)]
 #[allow(dead_code)]
 pub struct Compatibility {
   #[serde(default)]
@@ -18,7 +29,32 @@ pub struct Compatibility {
   support: HashMap<String, VersionSupport>,
 }
 
-#[derive(Debug, Deserialize, Clone, Serialize)]
+impl Compatibility {
+  pub fn description(&self) -> Option<&str> {
+    self.description.as_deref().filter(|d| !d.is_empty())
+  }
+
+  /// Compat data for a feature no shipping browser supports yet, so it's
+  /// reported as blocking every target rather than as having no data.
+  fn unsupported() -> Self {
+    let support = ["chrome", "firefox", "safari", "edge"]
+      .into_iter()
+      .map(|browser| {
+        let info = SupportInfo {
+          version_added: VersionAdded::Boolean(false),
+          extra: HashMap::new(),
+        };
+        (browser.to_string(), VersionSupport::Single(info))
+      })
+      .collect();
+    Self {
+      support,
+      ..Self::default()
+    }
+  }
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Serialize)]
 #[allow(dead_code)]
 pub struct Status {
   deprecated: bool,
@@ -52,78 +88,116 @@ pub enum VersionAdded {
 }
 
 static BCD_DATA: OnceCell<Arc<serde_json::Value>> = OnceCell::new();
+static BCD_FILE: OnceCell<PathBuf> = OnceCell::new();
+static CUSTOM_COMPAT_CACHE: Lazy<Mutex<HashMap<&'static str, Compatibility>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
 static FEATURE_COMPAT_CACHE: Lazy<HashMap<JsFeature, OnceCell<Compatibility>>> = Lazy::new(|| {
   let mut cache = HashMap::new();
-  for feature in [
-    JsFeature::OptionalChaining,
-    JsFeature::NullishCoalescing,
-    JsFeature::PrivateField,
-    JsFeature::PrivateMethod,
-    JsFeature::TopLevelAwait,
-    JsFeature::ClassStaticBlock,
-    JsFeature::LogicalAssignment,
-    JsFeature::NumericSeparator,
-    JsFeature::BigInt,
-    JsFeature::DynamicImport,
-    JsFeature::OptionalCatchBinding,
-    JsFeature::AsyncIteration,
-    JsFeature::RestSpread,
-    JsFeature::Await,
-    JsFeature::Decorator,
-    JsFeature::ServiceWorker,
-    JsFeature::PerformanceNow,
-    JsFeature::RequestIdleCallback,
-    JsFeature::TypedArray,
-    JsFeature::Int8Array,
-    JsFeature::Uint8Array,
-    JsFeature::Int16Array,
-    JsFeature::Uint16Array,
-    JsFeature::Int32Array,
-    JsFeature::Uint32Array,
-    JsFeature::Float32Array,
-    JsFeature::Float64Array,
-  ] {
-    cache.insert(feature, OnceCell::new());
+  for feature in JsFeature::ALL {
+    cache.insert(*feature, OnceCell::new());
   }
   cache
 });
 
-const BCD_CACHE_FILE: &str = ".jsco-cache/browser-compat-data.json";
-const FEATURE_CACHE_DIR: &str = ".jsco-cache/features";
+const BCD_CACHE_FILE: &str = "browser-compat-data.json";
+/// Prefix for keys of features BCD doesn't track, such as module-system
+/// signals. They have no browser support data.
+const LOCAL_KEY_PREFIX: &str = "jsco.";
+const FEATURE_CACHE_DIR: &str = "features";
 
 fn ensure_cache_dir(dir: &str) -> std::io::Result<PathBuf> {
-  let cache_dir = PathBuf::from(dir);
+  let cache_dir = cache::path(dir);
   if !cache_dir.exists() {
     fs::create_dir_all(&cache_dir)?;
   }
   Ok(cache_dir)
 }
 
-async fn download_bcd_data_async() -> Arc<serde_json::Value> {
-  if let Ok(data) = fs::read_to_string(BCD_CACHE_FILE) {
-    println!("Using cached BCD data");
+/// Drops the cached BCD data (and the per-feature entries derived from it) when
+/// it is older than `max_age`, so the next lookup fetches a fresh copy.
+/// Returns whether the cache was expired. Offline, stale data can't be
+/// replaced, so it's an error instead. A `--bcd-file` makes the cache moot.
+pub fn expire_stale_cache(max_age: Duration) -> Result<bool, JscoError> {
+  if BCD_FILE.get().is_some() {
+    return Ok(false);
+  }
+  let bcd_cache = cache::path(BCD_CACHE_FILE);
+  let is_stale = fs::metadata(&bcd_cache)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+    .and_then(|modified| modified.elapsed().ok())
+    .is_some_and(|age| age > max_age);
+  if !is_stale {
+    return Ok(false);
+  }
+  if is_offline() {
+    return Err(JscoError::StaleCache);
+  }
+  progress::status("Cached BCD data is older than allowed, refreshing");
+  let _ = fs::remove_file(&bcd_cache);
+  let _ = fs::remove_dir_all(cache::path(FEATURE_CACHE_DIR));
+  Ok(true)
+}
+
+/// Reads BCD data from a local `data.json` snapshot instead of the cache or
+/// the network. Call before the first lookup.
+pub fn use_bcd_file(path: impl Into<PathBuf>) -> bool {
+  BCD_FILE.set(path.into()).is_ok()
+}
+
+async fn download_bcd_data_async() -> Result<Arc<serde_json::Value>, JscoError> {
+  if let Some(path) = BCD_FILE.get() {
+    let data = fs::read_to_string(path)
+      .map_err(|err| JscoError::Read(format!("{}: {}", path.display(), err)))?;
+    let parsed_data =
+      serde_json::from_str(&data).map_err(|err| JscoError::Parse(err.to_string()))?;
+    return Ok(Arc::new(parsed_data));
+  }
+
+  if let Ok(data) = fs::read_to_string(cache::path(BCD_CACHE_FILE)) {
+    progress::status("Using cached BCD data");
     if let Ok(parsed_data) = serde_json::from_str(&data) {
-      return Arc::new(parsed_data);
+      return Ok(Arc::new(parsed_data));
     }
   }
 
+  if is_offline() {
+    return Err(JscoError::Offline);
+  }
   let data = download_with_progress(
     "https://cdn.jsdelivr.net/npm/@mdn/browser-compat-data/data.json".to_string(),
-    "browser-compat-data.json".to_string(),
+    BCD_CACHE_FILE.to_string(),
   )
   .await
-  .expect("Failed to download BCD data");
+  .map_err(|err| JscoError::Download(err.to_string()))?;
 
   let parsed_data: serde_json::Value =
-    serde_json::from_str(&data).expect("Failed to parse bcd data");
-  Arc::new(parsed_data)
+    serde_json::from_str(&data).map_err(|err| JscoError::Parse(err.to_string()))?;
+  Ok(Arc::new(parsed_data))
+}
+
+/// Loads BCD data ahead of analysis, so a failed download is returned as an
+/// error instead of surfacing halfway through a run.
+pub async fn load() -> Result<(), JscoError> {
+  if BCD_DATA.get().is_none() {
+    let data = download_bcd_data_async().await?;
+    let _ = BCD_DATA.set(data);
+  }
+  Ok(())
 }
 
-fn download_bcd_data() -> &'static Arc<serde_json::Value> {
-  BCD_DATA.get_or_init(|| {
-    tokio::task::block_in_place(|| {
-      tokio::runtime::Handle::current().block_on(download_bcd_data_async())
-    })
+/// BCD data for synchronous lookups. Loading it here blocks the current
+/// worker, which only a multi-threaded runtime allows; everywhere else
+/// `load()` has to have run first.
+fn download_bcd_data() -> Result<&'static Arc<serde_json::Value>, JscoError> {
+  BCD_DATA.get_or_try_init(|| {
+    let handle = tokio::runtime::Handle::try_current()
+      .ok()
+      .filter(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread)
+      .ok_or_else(|| {
+        JscoError::Download("BCD data isn't loaded; call bcd::load() first".to_string())
+      })?;
+    tokio::task::block_in_place(|| handle.block_on(download_bcd_data_async()))
   })
 }
 
@@ -146,68 +220,129 @@ fn get_version_added(support: Option<&VersionSupport>) -> Option<String> {
         })
     }
     Some(VersionSupport::Unknown(value)) => {
-      println!("Unknown version added: {:?}", value);
+      progress::status(format!("Unknown version added: {:?}", value));
       None
     }
     None => None,
   }
 }
 
-impl JsFeatureTrait for JsFeature {
-  fn compat(&self) -> Compatibility {
-    let compat = FEATURE_COMPAT_CACHE[self].get_or_init(|| {
-      // Try to load from feature cache first
-      let cache_file =
-        PathBuf::from(FEATURE_CACHE_DIR).join(format!("{}.json", self.key().replace('.', "_")));
-      if let Ok(data) = fs::read_to_string(&cache_file) {
-        if let Ok(compat) = serde_json::from_str(&data) {
-          return compat;
-        }
-      }
+/// Resolves the `__compat` entry for an arbitrary BCD key, going through the
+/// on-disk feature cache first.
+fn compat_for_key(key: &str) -> Result<Compatibility, JscoError> {
+  let cache_file = cache::path(FEATURE_CACHE_DIR).join(format!("{}.json", key.replace('.', "_")));
+  if let Ok(data) = fs::read_to_string(&cache_file) {
+    if let Ok(compat) = serde_json::from_str(&data) {
+      return Ok(compat);
+    }
+  }
 
-      // If not in cache, get from BCD data
-      let bcd = download_bcd_data();
-      let feature = bcd
-        .read_from_path(self.key())
-        .expect(&format!("Feature {} not found", self.key()))
-        .clone();
-      let compat: Compatibility =
-        serde_json::from_value(feature.get("__compat").unwrap().clone()).unwrap();
+  let bcd = download_bcd_data()?;
+  let compat: Compatibility = bcd
+    .read_from_path(key)
+    .and_then(|entry| entry.get("__compat"))
+    .and_then(|compat| serde_json::from_value(compat.clone()).ok())
+    .ok_or_else(|| JscoError::MissingFeature(key.to_string()))?;
 
-      // Save to feature cache
-      let _ = ensure_cache_dir(FEATURE_CACHE_DIR);
-      let _ = fs::write(cache_file, serde_json::to_string(&compat).unwrap());
+  // Save to feature cache
+  if cache::is_enabled() && ensure_cache_dir(FEATURE_CACHE_DIR).is_ok() {
+    let _ = fs::write(cache_file, serde_json::to_string(&compat).unwrap());
+  }
 
-      compat
-    });
+  Ok(compat)
+}
 
-    compat.clone()
+fn support_from_compat(compat: &Compatibility) -> BrowserSupport {
+  let mut support = HashMap::new();
+  for browser in ["chrome", "firefox", "safari", "edge"] {
+    if let Some(version) = get_version_added(compat.support.get(browser)) {
+      support.insert(browser.to_string(), version);
+    }
   }
+  support
+}
 
-  fn browser_support(&self) -> BrowserSupport {
-    let compat = self.compat();
+/// Looks up browser support for any BCD key, e.g.
+/// `javascript.operators.optional_chaining`, without building a report.
+/// `Ok(None)` means BCD has no such key; an error means BCD data couldn't be
+/// loaded at all.
+pub async fn feature_support(key: &str) -> Result<Option<BrowserSupport>, JscoError> {
+  load().await?;
+  match compat_for_key(key) {
+    Ok(compat) => Ok(Some(support_from_compat(&compat))),
+    Err(JscoError::MissingFeature(_)) => Ok(None),
+    Err(err) => Err(err),
+  }
+}
 
-    let mut support = HashMap::new();
-    if let Some(version) = get_version_added(compat.support.get("chrome")) {
-      support.insert("chrome".to_string(), version);
-    }
-    if let Some(version) = get_version_added(compat.support.get("firefox")) {
-      support.insert("firefox".to_string(), version);
+impl JsFeatureTrait for JsFeature {
+  fn try_compat(&self) -> Result<Compatibility, JscoError> {
+    if *self == JsFeature::Decorator {
+      return Ok(Compatibility::unsupported());
     }
-    if let Some(version) = get_version_added(compat.support.get("safari")) {
-      support.insert("safari".to_string(), version);
+    if self.key().starts_with(LOCAL_KEY_PREFIX) {
+      return Ok(Compatibility::default());
     }
-    if let Some(version) = get_version_added(compat.support.get("edge")) {
-      support.insert("edge".to_string(), version);
+    compat_for_key(self.key())
+  }
+
+  fn compat(&self) -> Compatibility {
+    if let JsFeature::Custom(key) = self {
+      // Unknown custom keys are reported without support data
+      return CUSTOM_COMPAT_CACHE
+        .lock()
+        .unwrap()
+        .entry(*key)
+        .or_insert_with(|| compat_for_key(key).unwrap_or_default())
+        .clone();
     }
 
-    support
+    let compat = FEATURE_COMPAT_CACHE[self].get_or_init(|| {
+      // Report the feature without support data rather than abort the run
+      self.try_compat().unwrap_or_else(|err| {
+        eprintln!("{} {}", style("⚠").yellow(), err);
+        Compatibility::default()
+      })
+    });
+
+    compat.clone()
+  }
+
+  fn browser_support(&self) -> BrowserSupport {
+    support_from_compat(&self.compat())
   }
 
   fn mdn_url(&self) -> String {
     let compat = self.compat();
-    compat.mdn_url.unwrap_or_default()
+    compat
+      .mdn_url
+      .filter(|url| !url.is_empty())
+      .unwrap_or_else(|| fallback_docs_url(self.key()))
+  }
+
+  fn is_deprecated(&self) -> bool {
+    self.compat().status.deprecated
   }
+
+  fn is_experimental(&self) -> bool {
+    self.compat().status.experimental
+  }
+}
+
+/// Builds an MDN search link for keys where BCD provides no `mdn_url`, so the
+/// documentation link in reports is never dead.
+fn fallback_docs_url(key: &str) -> String {
+  let parts = key.split('.').collect::<Vec<&str>>();
+  let term = match parts.as_slice() {
+    ["javascript", "builtins", rest @ ..] | ["api", rest @ ..] if !rest.is_empty() => {
+      rest.join(".")
+    }
+    _ => parts.last().unwrap_or(&key).replace('_', " "),
+  };
+  format!(
+    "https://developer.mozilla.org/en-US/search?q={}",
+    url::form_urlencoded::byte_serialize(term.as_bytes()).collect::<String>()
+  )
 }
 
 impl JsFeature {
@@ -217,17 +352,29 @@ impl JsFeature {
       JsFeature::NullishCoalescing => "javascript.operators.nullish_coalescing",
       JsFeature::PrivateField => "javascript.classes.private_class_fields",
       JsFeature::PrivateMethod => "javascript.classes.private_class_methods",
-      JsFeature::TopLevelAwait => "javascript.statements.top_level_await",
+      JsFeature::TopLevelAwait => "javascript.operators.await.top_level",
       JsFeature::ClassStaticBlock => "javascript.classes.class_static_block",
+      JsFeature::RelativeIndexing => "javascript.builtins.Array.at",
       JsFeature::LogicalAssignment => "javascript.operators.logical_assignment_operators",
       JsFeature::NumericSeparator => "javascript.operators.numeric_separators",
-      JsFeature::BigInt => "javascript.builtins.bigint",
+      JsFeature::BigInt => "javascript.builtins.BigInt",
       JsFeature::DynamicImport => "javascript.operators.import",
-      JsFeature::OptionalCatchBinding => "javascript.operators.optional_chaining",
+      JsFeature::OptionalCatchBinding => "javascript.statements.try...catch.optional_catch_binding",
+      JsFeature::ArrayFlat => "javascript.builtins.Array.flat",
+      JsFeature::ArrayFlatMap => "javascript.builtins.Array.flatMap",
+      JsFeature::ObjectFromEntries => "javascript.builtins.Object.fromEntries",
       JsFeature::AsyncIteration => "javascript.builtins.AsyncIterator",
+      JsFeature::AsyncGenerator => "javascript.statements.async_function*",
+      JsFeature::RegExpDotAll => "javascript.builtins.RegExp.dotAll",
       JsFeature::RestSpread => "javascript.operators.spread",
+      JsFeature::Exponentiation => "javascript.operators.exponentiation",
+      JsFeature::ExponentiationAssignment => "javascript.operators.exponentiation_assignment",
       JsFeature::Await => "javascript.operators.await",
-      JsFeature::Decorator => "javascript.builtins.decorators",
+      JsFeature::AsyncFunction => "javascript.statements.async_function",
+      JsFeature::ObjectEntries => "javascript.builtins.Object.entries",
+      JsFeature::ObjectValues => "javascript.builtins.Object.values",
+      // Not in BCD yet, and unsupported in every browser
+      JsFeature::Decorator => "jsco.decorators",
       JsFeature::ServiceWorker => "api.Worker",
       JsFeature::PerformanceNow => "api.Performance.now",
       JsFeature::RequestIdleCallback => "api.Window.requestIdleCallback",
@@ -240,6 +387,53 @@ impl JsFeature {
       JsFeature::Uint32Array => "javascript.builtins.Uint32Array",
       JsFeature::Float32Array => "javascript.builtins.Float32Array",
       JsFeature::Float64Array => "javascript.builtins.Float64Array",
+      JsFeature::Generator => "javascript.statements.function*",
+      JsFeature::RegExpUnicode => "javascript.builtins.RegExp.unicode",
+      JsFeature::ObjectAssign => "javascript.builtins.Object.assign",
+      JsFeature::Promise => "javascript.builtins.Promise",
+      JsFeature::PromiseFinally => "javascript.builtins.Promise.finally",
+      JsFeature::EnvironmentGlobal => "jsco.environment_globals",
+      JsFeature::ObjectGroupBy => "javascript.builtins.Object.groupBy",
+      JsFeature::MapGroupBy => "javascript.builtins.Map.groupBy",
+      JsFeature::DateTimeFormatRange => "javascript.builtins.Intl.DateTimeFormat.formatRange",
+      JsFeature::DateTimeFormatToParts => "javascript.builtins.Intl.DateTimeFormat.formatToParts",
+      JsFeature::NumberFormatRange => "javascript.builtins.Intl.NumberFormat.formatRange",
+      JsFeature::NumberFormatToParts => "javascript.builtins.Intl.NumberFormat.formatToParts",
+      JsFeature::NumberFormatCompactDisplay => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_compactDisplay_parameter"
+      }
+      JsFeature::NumberFormatCurrencySign => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_currencySign_parameter"
+      }
+      JsFeature::NumberFormatNotation => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_notation_parameter"
+      }
+      JsFeature::NumberFormatSignDisplay => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_signDisplay_parameter"
+      }
+      JsFeature::NumberFormatUnit => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_unit_parameter"
+      }
+      JsFeature::NumberFormatUnitDisplay => {
+        "javascript.builtins.Intl.NumberFormat.NumberFormat.options_unitDisplay_parameter"
+      }
+      JsFeature::Escape => "javascript.builtins.escape",
+      JsFeature::Unescape => "javascript.builtins.unescape",
+      JsFeature::DocumentAll => "api.Document.all",
+      JsFeature::StructuredClone => "api.structuredClone",
+      JsFeature::QueueMicrotask => "api.queueMicrotask",
+      JsFeature::ReportError => "api.reportError",
+      JsFeature::ArrayIsArray => "javascript.builtins.Array.isArray",
+      JsFeature::ObjectKeys => "javascript.builtins.Object.keys",
+      JsFeature::ClassSyntax => "javascript.classes",
+      JsFeature::CommonJs => "jsco.commonjs",
+      JsFeature::ArraySortStability => "javascript.builtins.Array.sort.stable_sorting",
+      JsFeature::WebAssembly => "javascript.builtins.WebAssembly",
+      JsFeature::ObjectDefineProperty => "javascript.builtins.Object.defineProperty",
+      JsFeature::ObjectCreate => "javascript.builtins.Object.create",
+      JsFeature::Destructuring => "javascript.operators.destructuring",
+      JsFeature::SymbolIterator => "javascript.builtins.Symbol.iterator",
+      JsFeature::Custom(key) => key,
     }
   }
 }
@@ -251,10 +445,15 @@ pub trait JsonRead {
 impl JsonRead for serde_json::Value {
   // read data from deep structure
   fn read_from_path(&self, path: &str) -> Option<&serde_json::Value> {
-    let parts = path.split('.').collect::<Vec<&str>>();
+    // Some BCD names contain an ellipsis, e.g. `statements.try...catch`
+    let parts = path
+      .replace("...", "\0")
+      .split('.')
+      .map(|part| part.replace('\0', "..."))
+      .collect::<Vec<String>>();
     let mut current = self;
     for part in parts {
-      current = current.get(part)?;
+      current = current.get(&part)?;
     }
     Some(current)
   }
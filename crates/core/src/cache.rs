@@ -0,0 +1,60 @@
+use console::style;
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+  },
+};
+
+/// Fallback when the OS has no per-user cache directory.
+pub const CACHE_DIR: &str = ".jsco-cache";
+/// Environment variable overriding the cache directory.
+pub const CACHE_DIR_ENV: &str = "JSCO_CACHE_DIR";
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Uses `dir` for the cache instead of the default. Has no effect once the
+/// cache directory has been resolved, so call it before anything is cached.
+pub fn set_dir(dir: impl Into<PathBuf>) -> bool {
+  DIR.set(dir.into()).is_ok()
+}
+
+/// The cache directory: the one given to `set_dir`, else `$JSCO_CACHE_DIR`,
+/// else `jsco` in the OS cache directory, else `.jsco-cache`.
+pub fn dir() -> &'static Path {
+  DIR.get_or_init(|| {
+    std::env::var_os(CACHE_DIR_ENV)
+      .map(PathBuf::from)
+      .or_else(|| dirs::cache_dir().map(|dir| dir.join("jsco")))
+      .unwrap_or_else(|| PathBuf::from(CACHE_DIR))
+  })
+}
+
+/// Creates the cache directory. If that fails, caching is disabled for the
+/// rest of the run instead of failing later on the first write.
+pub fn init() -> bool {
+  if let Err(err) = fs::create_dir_all(dir()) {
+    eprintln!(
+      "{} Failed to create cache directory {}: {}. Caching is disabled for this run.",
+      style("⚠").yellow(),
+      dir().display(),
+      err
+    );
+    DISABLED.store(true, Ordering::Relaxed);
+    return false;
+  }
+  true
+}
+
+pub fn is_enabled() -> bool {
+  !DISABLED.load(Ordering::Relaxed)
+}
+
+/// Path of an entry inside the cache directory.
+pub fn path(name: &str) -> PathBuf {
+  dir().join(name)
+}
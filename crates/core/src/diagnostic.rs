@@ -0,0 +1,30 @@
+use oxc::span::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::feature::{LineIndex, Location};
+
+/// A finding that isn't a browser-compat feature: misuse, migration hints and
+/// other advisories surfaced alongside the feature report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+  pub code: String,
+  pub message: String,
+  #[serde(skip)]
+  pub span: Span,
+  pub location: Location,
+}
+
+impl Diagnostic {
+  pub fn new(code: &str, message: impl Into<String>, span: Span) -> Self {
+    Self {
+      code: code.to_string(),
+      message: message.into(),
+      span,
+      location: Location::new(span, None),
+    }
+  }
+
+  pub fn prepare_output(&mut self, source_code: &str, lines: &LineIndex) {
+    self.location.resolve(self.span, source_code, lines);
+  }
+}
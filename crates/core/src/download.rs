@@ -2,21 +2,33 @@ use futures_util::StreamExt;
 use reqwest::Client;
 use std::{
   fs::{self},
-  io::{stdout, Write},
-  path::PathBuf,
-  sync::OnceLock,
+  io::{stderr, Write},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+  },
 };
 
-const CACHE_DIR: &str = ".jsco-cache";
+use crate::{cache, progress};
+
 static CLIENT: OnceLock<Client> = OnceLock::new();
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Disables network access: only cached downloads are served.
+pub fn set_offline(offline: bool) {
+  OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+  OFFLINE.load(Ordering::Relaxed)
+}
 
 async fn get_cached_content(key: &str) -> Option<String> {
-  let cache_dir = PathBuf::from(CACHE_DIR);
-  if !cache_dir.exists() {
-    fs::create_dir(&cache_dir).ok()?;
+  if !cache::is_enabled() {
+    return None;
   }
 
-  let cache_file = cache_dir.join(key);
+  let cache_file = cache::path(key);
   if cache_file.exists() {
     fs::read_to_string(cache_file).ok()
   } else {
@@ -25,14 +37,11 @@ async fn get_cached_content(key: &str) -> Option<String> {
 }
 
 async fn save_to_cache(key: &str, content: &str) -> Result<(), std::io::Error> {
-  let cache_dir = PathBuf::from(CACHE_DIR);
-  if !cache_dir.exists() {
-    fs::create_dir(&cache_dir)?;
+  if !cache::is_enabled() {
+    return Ok(());
   }
 
-  let cache_file = cache_dir.join(key);
-  fs::write(cache_file, content)?;
-  Ok(())
+  fs::write(cache::path(key), content)
 }
 
 pub async fn download_with_progress(
@@ -40,9 +49,12 @@ pub async fn download_with_progress(
   cache_key: String,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
   if let Some(cached) = get_cached_content(&cache_key).await {
-    println!("Using cached version of {}", url);
+    progress::status(format!("Using cached version of {}", url));
     return Ok(cached);
   }
+  if is_offline() {
+    return Err(format!("{} isn't cached and offline mode is on", url).into());
+  }
 
   let res = CLIENT
     .get_or_init(|| Client::new())
@@ -51,23 +63,24 @@ pub async fn download_with_progress(
     .await?;
   let total_size = res.content_length().unwrap_or(0);
   let mut downloaded = 0;
-  let mut content = String::new();
+  // Chunks can split a multibyte character, so decode only once at the end
+  let mut bytes: Vec<u8> = Vec::with_capacity(total_size as usize);
 
-  print!("Preparing to download {}...\n", url);
-  stdout().flush()?;
+  progress::status(format!("Preparing to download {}...", url));
+  let draw_bar = total_size > 0 && progress::draws_inline();
 
   let mut stream = res.bytes_stream();
   while let Some(chunk) = stream.next().await {
     let chunk = chunk?;
     downloaded += chunk.len() as u64;
-    content.push_str(&String::from_utf8_lossy(&chunk));
+    bytes.extend_from_slice(&chunk);
 
-    if total_size > 0 {
+    if draw_bar {
       let progress = (downloaded as f64 / total_size as f64) * 100.0;
       let width = 40;
       let filled = (width as f64 * progress / 100.0) as usize;
       let empty = width - filled;
-      print!(
+      eprint!(
         "\r{} {:3.1}% [{:█<filled$}{:⋅<empty$}]",
         "Downloading",
         progress,
@@ -76,11 +89,18 @@ pub async fn download_with_progress(
         filled = filled,
         empty = empty
       );
-      stdout().flush()?;
+      stderr().flush()?;
     }
   }
-  println!("\nDownload completed!");
+  if draw_bar {
+    eprintln!();
+  }
+  progress::status("Download completed!");
+  let content = String::from_utf8_lossy(&bytes).into_owned();
 
-  save_to_cache(&cache_key, &content).await?;
+  // A failed cache write shouldn't throw away a successful download
+  if let Err(err) = save_to_cache(&cache_key, &content).await {
+    eprintln!("Failed to cache {}: {}", url, err);
+  }
   Ok(content)
 }
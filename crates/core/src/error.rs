@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// Why an analysis couldn't run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JscoError {
+  /// Browser compat data couldn't be fetched.
+  Download(String),
+  /// Browser compat data was fetched but isn't valid JSON.
+  Parse(String),
+  /// The local BCD file given with `--bcd-file` couldn't be read.
+  Read(String),
+  /// Offline mode is on and no BCD data is cached.
+  Offline,
+  /// Offline mode is on and the cached BCD data is older than allowed.
+  StaleCache,
+  /// A BCD key has no `__compat` entry.
+  MissingFeature(String),
+}
+
+impl fmt::Display for JscoError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      JscoError::Download(err) => write!(f, "failed to download BCD data: {}", err),
+      JscoError::Parse(err) => write!(f, "failed to parse BCD data: {}", err),
+      JscoError::Read(err) => write!(f, "failed to read BCD file: {}", err),
+      JscoError::Offline => write!(
+        f,
+        "no cached BCD data and downloads are disabled in offline mode; pass a local copy with --bcd-file"
+      ),
+      JscoError::StaleCache => write!(
+        f,
+        "cached BCD data is older than allowed and can't be refreshed in offline mode; raise --max-bcd-age or pass a local copy with --bcd-file"
+      ),
+      JscoError::MissingFeature(key) => write!(f, "feature {} not found in BCD data", key),
+    }
+  }
+}
+
+impl std::error::Error for JscoError {}
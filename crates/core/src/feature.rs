@@ -1,9 +1,12 @@
+use once_cell::sync::Lazy;
 use oxc::span::Span;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 use crate::bcd::Compatibility;
+use crate::error::JscoError;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum JsFeature {
@@ -14,6 +17,8 @@ pub enum JsFeature {
   // ES2022+
   TopLevelAwait,
   ClassStaticBlock,
+  // `.at(-1)` on arrays and strings
+  RelativeIndexing,
   // ES2021
   LogicalAssignment,
   NumericSeparator,
@@ -22,11 +27,22 @@ pub enum JsFeature {
   DynamicImport,
   // ES2019
   OptionalCatchBinding,
+  ArrayFlat,
+  ArrayFlatMap,
+  ObjectFromEntries,
   // ES2018
   AsyncIteration,
+  AsyncGenerator,
+  RegExpDotAll,
   RestSpread,
+  // ES2016
+  Exponentiation,
+  ExponentiationAssignment,
   // ES2017
   Await,
+  AsyncFunction,
+  ObjectEntries,
+  ObjectValues,
   Decorator,
   ServiceWorker,
   // performance.now()
@@ -44,8 +60,253 @@ pub enum JsFeature {
   Uint32Array,
   Float32Array,
   Float64Array,
+  // ES2015
+  Generator,
+  RegExpUnicode,
+  ObjectAssign,
+  Promise,
+  // ES2018
+  PromiseFinally,
+  // Opt-in portability lint: `window`/`self`/`global`/`process`
+  EnvironmentGlobal,
+  // ES2024
+  ObjectGroupBy,
+  MapGroupBy,
+  // Intl method-level support
+  DateTimeFormatRange,
+  DateTimeFormatToParts,
+  NumberFormatRange,
+  NumberFormatToParts,
+  // Options of the ES2020 unified `Intl.NumberFormat`, e.g. `{ notation }`
+  NumberFormatCompactDisplay,
+  NumberFormatCurrencySign,
+  NumberFormatNotation,
+  NumberFormatSignDisplay,
+  NumberFormatUnit,
+  NumberFormatUnitDisplay,
+  // Opt-in legacy APIs
+  Escape,
+  Unescape,
+  DocumentAll,
+  // Modern global functions
+  StructuredClone,
+  QueueMicrotask,
+  ReportError,
+  // Opt-in ES5 builtins for IE-era baselines
+  ArrayIsArray,
+  ObjectKeys,
+  // ES2015
+  ClassSyntax,
+  // Opt-in module-system signal: `require(...)`, `module.exports`, `exports.x`
+  CommonJs,
+  // Opt-in, informational: sort stability is only guaranteed since ES2019
+  ArraySortStability,
+  WebAssembly,
+  // Opt-in ES5 object APIs for ES3-era baselines
+  ObjectDefineProperty,
+  ObjectCreate,
+  // ES2015
+  Destructuring,
+  // `[Symbol.iterator]()` methods
+  SymbolIterator,
+  /// A user-supplied method detector, identified by its BCD key
+  Custom(&'static str),
+}
+
+impl JsFeature {
+  pub const ALL: &'static [JsFeature] = &[
+    JsFeature::OptionalChaining,
+    JsFeature::NullishCoalescing,
+    JsFeature::PrivateField,
+    JsFeature::PrivateMethod,
+    JsFeature::TopLevelAwait,
+    JsFeature::ClassStaticBlock,
+    JsFeature::RelativeIndexing,
+    JsFeature::LogicalAssignment,
+    JsFeature::NumericSeparator,
+    JsFeature::BigInt,
+    JsFeature::DynamicImport,
+    JsFeature::OptionalCatchBinding,
+    JsFeature::ArrayFlat,
+    JsFeature::ArrayFlatMap,
+    JsFeature::ObjectFromEntries,
+    JsFeature::AsyncIteration,
+    JsFeature::AsyncGenerator,
+    JsFeature::RegExpDotAll,
+    JsFeature::RestSpread,
+    JsFeature::Exponentiation,
+    JsFeature::ExponentiationAssignment,
+    JsFeature::Await,
+    JsFeature::AsyncFunction,
+    JsFeature::ObjectEntries,
+    JsFeature::ObjectValues,
+    JsFeature::Decorator,
+    JsFeature::ServiceWorker,
+    JsFeature::PerformanceNow,
+    JsFeature::RequestIdleCallback,
+    JsFeature::TypedArray,
+    JsFeature::Int8Array,
+    JsFeature::Uint8Array,
+    JsFeature::Int16Array,
+    JsFeature::Uint16Array,
+    JsFeature::Int32Array,
+    JsFeature::Uint32Array,
+    JsFeature::Float32Array,
+    JsFeature::Float64Array,
+    JsFeature::Generator,
+    JsFeature::RegExpUnicode,
+    JsFeature::ObjectAssign,
+    JsFeature::Promise,
+    JsFeature::PromiseFinally,
+    JsFeature::EnvironmentGlobal,
+    JsFeature::ObjectGroupBy,
+    JsFeature::MapGroupBy,
+    JsFeature::DateTimeFormatRange,
+    JsFeature::DateTimeFormatToParts,
+    JsFeature::NumberFormatRange,
+    JsFeature::NumberFormatToParts,
+    JsFeature::NumberFormatCompactDisplay,
+    JsFeature::NumberFormatCurrencySign,
+    JsFeature::NumberFormatNotation,
+    JsFeature::NumberFormatSignDisplay,
+    JsFeature::NumberFormatUnit,
+    JsFeature::NumberFormatUnitDisplay,
+    JsFeature::Escape,
+    JsFeature::Unescape,
+    JsFeature::DocumentAll,
+    JsFeature::StructuredClone,
+    JsFeature::QueueMicrotask,
+    JsFeature::ReportError,
+    JsFeature::ArrayIsArray,
+    JsFeature::ObjectKeys,
+    JsFeature::ClassSyntax,
+    JsFeature::CommonJs,
+    JsFeature::ArraySortStability,
+    JsFeature::WebAssembly,
+    JsFeature::ObjectDefineProperty,
+    JsFeature::ObjectCreate,
+    JsFeature::Destructuring,
+    JsFeature::SymbolIterator,
+  ];
+
+  /// Features that are only reported when explicitly enabled.
+  pub fn is_opt_in(&self) -> bool {
+    matches!(
+      self,
+      JsFeature::EnvironmentGlobal
+        | JsFeature::Escape
+        | JsFeature::Unescape
+        | JsFeature::DocumentAll
+        | JsFeature::ArrayIsArray
+        | JsFeature::ObjectKeys
+        | JsFeature::CommonJs
+        | JsFeature::ArraySortStability
+        | JsFeature::ObjectDefineProperty
+        | JsFeature::ObjectCreate
+    )
+  }
+
+  /// Features that point at a possible footgun rather than a missing API, so
+  /// they are reported as `Info` regardless of target support.
+  pub fn is_informational(&self) -> bool {
+    matches!(self, JsFeature::ArraySortStability)
+  }
+
+  /// The ES edition that standardized the feature, `None` for web platform
+  /// APIs, Intl methods and proposals. ES5 is reported as 2009.
+  pub fn es_year(&self) -> Option<u16> {
+    match self {
+      JsFeature::ArrayIsArray
+      | JsFeature::ObjectKeys
+      | JsFeature::ObjectDefineProperty
+      | JsFeature::ObjectCreate => Some(2009),
+      JsFeature::TypedArray
+      | JsFeature::Int8Array
+      | JsFeature::Uint8Array
+      | JsFeature::Int16Array
+      | JsFeature::Uint16Array
+      | JsFeature::Int32Array
+      | JsFeature::Uint32Array
+      | JsFeature::Float32Array
+      | JsFeature::Float64Array
+      | JsFeature::Generator
+      | JsFeature::RegExpUnicode
+      | JsFeature::ObjectAssign
+      | JsFeature::Promise
+      | JsFeature::ClassSyntax
+      | JsFeature::Destructuring
+      | JsFeature::SymbolIterator => Some(2015),
+      JsFeature::Exponentiation | JsFeature::ExponentiationAssignment => Some(2016),
+      JsFeature::Await
+      | JsFeature::AsyncFunction
+      | JsFeature::ObjectEntries
+      | JsFeature::ObjectValues => Some(2017),
+      JsFeature::AsyncIteration
+      | JsFeature::AsyncGenerator
+      | JsFeature::RegExpDotAll
+      | JsFeature::RestSpread
+      | JsFeature::PromiseFinally => Some(2018),
+      JsFeature::OptionalCatchBinding
+      | JsFeature::ArrayFlat
+      | JsFeature::ArrayFlatMap
+      | JsFeature::ObjectFromEntries
+      | JsFeature::ArraySortStability => Some(2019),
+      JsFeature::OptionalChaining
+      | JsFeature::NullishCoalescing
+      | JsFeature::BigInt
+      | JsFeature::DynamicImport => Some(2020),
+      JsFeature::LogicalAssignment | JsFeature::NumericSeparator => Some(2021),
+      JsFeature::PrivateField
+      | JsFeature::PrivateMethod
+      | JsFeature::TopLevelAwait
+      | JsFeature::ClassStaticBlock
+      | JsFeature::RelativeIndexing => Some(2022),
+      JsFeature::ObjectGroupBy | JsFeature::MapGroupBy => Some(2024),
+      _ => None,
+    }
+  }
+
+  /// Resolves a feature from its enum name (`OptionalChaining`,
+  /// `optional_chaining`) or its BCD key.
+  pub fn from_name(name: &str) -> Option<JsFeature> {
+    let normalize = |s: &str| s.replace(['_', '-'], "").to_lowercase();
+    let wanted = normalize(name);
+    JsFeature::ALL
+      .iter()
+      .find(|feature| feature.key() == name || normalize(&format!("{:?}", feature)) == wanted)
+      .copied()
+  }
+
+  /// The feature with BCD key `key`: a built-in one if it has a detector,
+  /// `Custom` otherwise.
+  pub fn from_key(key: &str) -> JsFeature {
+    JsFeature::ALL
+      .iter()
+      .find(|feature| feature.key() == key)
+      .copied()
+      .unwrap_or_else(|| JsFeature::custom(key))
+  }
+
+  /// A `Custom` feature for `key`. Each distinct key is allocated once for
+  /// the life of the process.
+  pub fn custom(key: &str) -> JsFeature {
+    let mut keys = CUSTOM_KEYS.lock().unwrap();
+    let key = match keys.get(key) {
+      Some(key) => *key,
+      None => {
+        let key: &'static str = Box::leak(key.to_string().into_boxed_str());
+        keys.insert(key);
+        key
+      }
+    };
+    JsFeature::Custom(key)
+  }
 }
 
+/// Keys of custom features, leaked once each so `JsFeature::Custom` can stay
+/// `Copy` like the built-in variants.
+static CUSTOM_KEYS: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Default::default);
+
 impl Serialize for JsFeature {
   fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
   where
@@ -55,25 +316,166 @@ impl Serialize for JsFeature {
   }
 }
 
+impl<'de> Deserialize<'de> for JsFeature {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    String::deserialize(deserializer).map(|key| JsFeature::from_key(&key))
+  }
+}
+
+/// Reads a value shared behind `Arc<Mutex<_>>`, the inverse of serializing
+/// through the lock.
+pub(crate) fn deserialize_shared<'de, D, T>(deserializer: D) -> Result<Arc<Mutex<T>>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  T::deserialize(deserializer).map(|value| Arc::new(Mutex::new(value)))
+}
+
 pub type BrowserSupport = HashMap<String, String>;
 
-#[derive(Debug, Clone, Serialize)]
+/// How seriously a finding should be treated. `Off` hides it entirely and
+/// `Error` marks features the targets don't support; configuring a feature
+/// as `Error` makes the CLI exit non-zero.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+  Off,
+  Info,
+  Warn,
+  Error,
+}
+
+impl FromStr for Severity {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "off" => Ok(Severity::Off),
+      "info" => Ok(Severity::Info),
+      "warn" | "warning" => Ok(Severity::Warn),
+      "error" => Ok(Severity::Error),
+      _ => Err(format!("unknown severity: {}", s)),
+    }
+  }
+}
+
+/// The ES edition that introduced an `Array.prototype` method, used to find
+/// the floor a method chain requires. ES5 is reported as 2009.
+pub fn array_method_es_year(method: &str) -> Option<u16> {
+  match method {
+    "map" | "filter" | "reduce" | "reduceRight" | "forEach" | "some" | "every" | "indexOf"
+    | "lastIndexOf" => Some(2009),
+    "find" | "findIndex" | "fill" | "copyWithin" | "entries" | "keys" | "values" => Some(2015),
+    "includes" => Some(2016),
+    "flat" | "flatMap" => Some(2019),
+    "at" => Some(2022),
+    "findLast" | "findLastIndex" | "toSorted" | "toReversed" | "toSpliced" | "with" => Some(2023),
+    _ => None,
+  }
+}
+
+/// Formats an ES year the way people say it: 2009 is "ES5", later editions
+/// are "ES2015" and so on.
+pub fn es_year_label(year: u16) -> String {
+  if year <= 2009 {
+    "ES5".to_string()
+  } else {
+    format!("ES{}", year)
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureReport {
   pub feat_type: JsFeature,
+  /// Spans of the findings, parallel to `locations`. Empty in deserialized
+  /// reports, whose locations are already resolved.
   #[serde(skip)]
   pub found_in: Vec<Span>,
+  /// The spans in `found_in`, for constant-time duplicate checks.
+  #[serde(skip)]
+  seen: HashSet<Span>,
   #[serde(rename = "locations")]
   pub locations: Vec<Location>,
-  #[serde(serialize_with = "serialize_browser_support")]
+  #[serde(
+    serialize_with = "serialize_browser_support",
+    deserialize_with = "deserialize_shared"
+  )]
   pub support: Arc<Mutex<BrowserSupport>>,
   pub mdn_url: String,
+  pub deprecated: bool,
+  pub experimental: bool,
+  /// Assigned by the caller once targets are known; the core has no say in
+  /// how seriously a finding is taken.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub severity: Option<Severity>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Location {
+  /// Byte offsets into the source.
   pub start: usize,
   pub end: usize,
+  /// 1-based lines and columns, filled in by `prepare_output`. Columns count
+  /// bytes, which matches characters for ASCII sources.
+  pub start_line: usize,
+  pub start_col: usize,
+  pub end_line: usize,
+  pub end_col: usize,
   pub code: String,
+  /// Extra context for the match, e.g. which global was referenced.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub detail: Option<String>,
+}
+
+impl Location {
+  pub fn new(span: Span, detail: Option<String>) -> Self {
+    Self {
+      start: span.start as usize,
+      end: span.end as usize,
+      start_line: 0,
+      start_col: 0,
+      end_line: 0,
+      end_col: 0,
+      code: String::new(),
+      detail,
+    }
+  }
+
+  /// Fills in the snippet and line/column positions for `span`.
+  pub fn resolve(&mut self, span: Span, source_code: &str, lines: &LineIndex) {
+    self.code = span.source_text(source_code).to_string();
+    (self.start_line, self.start_col) = lines.position(self.start);
+    (self.end_line, self.end_col) = lines.position(self.end);
+  }
+}
+
+/// Byte offsets of line starts, for turning offsets into line/column pairs.
+pub struct LineIndex {
+  starts: Vec<usize>,
+}
+
+impl LineIndex {
+  pub fn new(source_code: &str) -> Self {
+    let mut starts = vec![0];
+    starts.extend(
+      source_code
+        .bytes()
+        .enumerate()
+        .filter(|(_, byte)| *byte == b'\n')
+        .map(|(i, _)| i + 1),
+    );
+    Self { starts }
+  }
+
+  /// 1-based line and column of a byte offset.
+  pub fn position(&self, offset: usize) -> (usize, usize) {
+    let line = self.starts.partition_point(|&start| start <= offset);
+    (line, offset - self.starts[line - 1] + 1)
+  }
 }
 
 fn serialize_browser_support<S>(
@@ -91,32 +493,57 @@ impl FeatureReport {
     Self {
       feat_type,
       found_in: Vec::new(),
+      seen: HashSet::new(),
       locations: Vec::new(),
       support: Arc::new(Mutex::new(support)),
       mdn_url: String::new(),
+      deprecated: false,
+      experimental: false,
+      severity: None,
     }
   }
 
   pub fn add_span(&mut self, span: Span) {
+    self.add_span_with_detail(span, None);
+  }
+
+  pub fn add_span_with_detail(&mut self, span: Span, detail: Option<String>) {
+    // The same node can be reached from more than one AST arm
+    if !self.seen.insert(span) {
+      return;
+    }
     self.found_in.push(span);
-    self.locations.push(Location {
-      start: span.start as usize,
-      end: span.end as usize,
-      code: String::new(),
-    });
+    self.locations.push(Location::new(span, detail));
   }
 
-  pub fn prepare_output(&mut self, source_code: &str) {
-    for (i, span) in self.found_in.iter().enumerate() {
-      if let Some(location) = self.locations.get_mut(i) {
-        location.code = span.source_text(source_code).to_string();
-      }
+  /// Keeps only the spans (and their locations) matching `keep`.
+  pub fn retain_spans(&mut self, mut keep: impl FnMut(&Span) -> bool) {
+    let kept = self
+      .found_in
+      .iter()
+      .map(|span| keep(span))
+      .collect::<Vec<bool>>();
+    let mut flags = kept.iter();
+    self.found_in.retain(|_| *flags.next().unwrap());
+    let mut flags = kept.iter();
+    self.locations.retain(|_| *flags.next().unwrap());
+    self.seen = self.found_in.iter().copied().collect();
+  }
+
+  pub fn prepare_output(&mut self, source_code: &str, lines: &LineIndex) {
+    for (span, location) in self.found_in.iter().zip(self.locations.iter_mut()) {
+      location.resolve(*span, source_code, lines);
     }
   }
 }
 
 pub trait JsFeatureTrait {
+  /// The BCD entry, or why it couldn't be loaded.
+  fn try_compat(&self) -> Result<Compatibility, JscoError>;
+  /// Like `try_compat`, but falls back to an entry without support data.
   fn compat(&self) -> Compatibility;
   fn browser_support(&self) -> BrowserSupport;
   fn mdn_url(&self) -> String;
+  fn is_deprecated(&self) -> bool;
+  fn is_experimental(&self) -> bool;
 }
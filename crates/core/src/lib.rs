@@ -1,249 +1,412 @@
 use console::style;
-use glob::glob;
-use indicatif::{ProgressBar, ProgressStyle};
+use futures_util::{stream, StreamExt};
+use glob::Pattern;
 use md5;
 use std::{
-  fs,
-  path::PathBuf,
-  time::{SystemTime, UNIX_EPOCH},
+  collections::HashMap,
+  path::Path,
+  sync::{Arc, Mutex, OnceLock},
+  time::{Duration, Instant},
 };
-use url::Url;
 
-use download::download_with_progress;
-use report::{Report, Reports};
-use tokio::sync::mpsc;
+pub use bcd::feature_support;
+pub use error::JscoError;
+pub use options::{CustomMethod, Options, SourceKind};
+use profile::Profile;
+pub use progress::set_quiet;
+use progress::Progress;
+use report::{CheckTimings, Report, Reports};
+pub use source::{FsProvider, MemoryProvider, SourceProvider};
+use tokio::{sync::mpsc, task::JoinHandle};
 
 pub mod bcd;
+pub mod cache;
+pub mod diagnostic;
 pub mod download;
+pub mod error;
 pub mod feature;
+pub mod options;
+pub mod profile;
+mod progress;
 pub mod report;
+pub mod source;
+pub mod version;
 
-const CACHE_DIR: &str = ".jsco-cache";
+/// Identifies a source for within-run memoization. The extension is part of
+/// the key because it decides how the source is parsed.
+fn analysis_key(path: &str, source_code: &str) -> String {
+  let extension = Path::new(path)
+    .extension()
+    .map(|ext| ext.to_string_lossy().to_string())
+    .unwrap_or_default();
+  format!("{:x}.{}", md5::compute(source_code), extension)
+}
 
-fn get_cache_key(url: &str) -> String {
-  if let Ok(parsed_url) = Url::parse(url) {
-    let base_url = format!(
-      "{}://{}{}",
-      parsed_url.scheme(),
-      parsed_url.host_str().unwrap_or(""),
-      parsed_url.path()
-    );
-    format!("{:x}", md5::compute(base_url))
-  } else {
-    format!("{:x}", md5::compute(url))
-  }
+fn ignore_patterns(patterns: &[String]) -> Vec<Pattern> {
+  patterns
+    .iter()
+    .filter_map(|pattern| match Pattern::new(pattern) {
+      Ok(pattern) => Some(pattern),
+      Err(err) => {
+        eprintln!(
+          "{} Ignoring invalid pattern {}: {}",
+          style("⚠").yellow(),
+          pattern,
+          err
+        );
+        None
+      }
+    })
+    .collect()
 }
 
-#[derive(Debug)]
-enum InputType {
-  File(PathBuf),
-  Url(String),
-  Directory(PathBuf),
-  Glob(String),
+/// Analyzes one in-memory source. `filename` only decides how the code is
+/// parsed and the source itself is never read from disk. BCD data is loaded
+/// first if needed, from the cache or the network; no progress or summary is
+/// printed.
+pub async fn analyze_source(filename: &str, code: &str) -> Result<Report, JscoError> {
+  bcd::load().await?;
+  let mut report = Report::new(filename.to_string(), code.to_string());
+  report.check_feature_with(&Options::default());
+  report.prepare_output();
+  Ok(report)
 }
 
-impl InputType {
-  fn from_str(s: &str) -> Self {
-    if s.starts_with("http://") || s.starts_with("https://") {
-      Self::Url(s.to_string())
-    } else if s.contains('*') {
-      Self::Glob(s.to_string())
-    } else if PathBuf::from(s).is_dir() {
-      Self::Directory(PathBuf::from(s))
-    } else {
-      Self::File(PathBuf::from(s))
-    }
-  }
+/// Analyzes a whole source, since findings depend on surrounding scope, but
+/// reports only what intersects the byte range `start..end`. Meant for editor
+/// integrations re-checking a single edited region.
+/// Like `analyze_source`, BCD data is loaded first if needed.
+pub async fn analyze_range(
+  path: String,
+  source_code: String,
+  start: usize,
+  end: usize,
+) -> Result<Report, JscoError> {
+  bcd::load().await?;
+  let mut report = Report::new(path, source_code);
+  report.check_feature_with(&Options::default());
+  report.retain_range(start, end);
+  report.prepare_output();
+  Ok(report)
+}
+
+/// The outcome of a run over several inputs.
+#[derive(Debug, Default)]
+pub struct Analysis {
+  /// One report per source, sorted by path.
+  pub reports: Reports,
+  /// Set when `max_findings` stopped the run before every source was
+  /// analyzed, so `reports` is incomplete.
+  pub truncated: bool,
+}
+
+pub async fn jsco(inputs: Vec<String>) -> Result<Reports, JscoError> {
+  // No `max_findings`, so the run is never truncated
+  Ok(jsco_with_options(inputs, Options::default()).await?.reports)
+}
+
+pub async fn jsco_with_options(
+  inputs: Vec<String>,
+  options: Options,
+) -> Result<Analysis, JscoError> {
+  jsco_with_callback(inputs, options, |_| {}).await
+}
+
+/// Like `jsco_with_options`, but calls `on_report` as each file finishes, in
+/// completion order, so results can be shown before the whole run is done.
+/// The returned reports are still sorted by path.
+pub async fn jsco_with_callback(
+  inputs: Vec<String>,
+  options: Options,
+  mut on_report: impl FnMut(&Report) + Send,
+) -> Result<Analysis, JscoError> {
+  // Sources come from disk here, so their package.json is there too
+  let options = Options {
+    read_package_json: true,
+    ..options
+  };
+  let provider = Arc::new(FsProvider {
+    quiet: options.quiet,
+    extensions: options.extensions.clone(),
+    refresh: options.refresh,
+  });
+  run(inputs, options, provider, &mut on_report).await
 }
 
-pub async fn jsco(inputs: Vec<String>) -> Reports {
-  let cache_dir = PathBuf::from(CACHE_DIR);
-  if !cache_dir.exists() {
-    let _ = fs::create_dir(&cache_dir);
+/// Analyzes one source, reusing the findings of an identical source seen
+/// earlier in the run. Timings are `None` for reused reports.
+fn analyze(
+  path: String,
+  source_code: String,
+  options: &Options,
+  analyzed: &Mutex<HashMap<String, Arc<OnceLock<Report>>>>,
+) -> (Report, Option<CheckTimings>) {
+  // Content hash + extension -> findings of the first report with that
+  // source, so the same source seen twice in one run is only parsed once.
+  // Concurrent workers with the same source wait on the first one's cell
+  let key = analysis_key(&path, &source_code);
+  let cell = analyzed.lock().unwrap().entry(key).or_default().clone();
+  let mut timings = None;
+  let original = cell.get_or_init(|| {
+    let mut report = Report::new(path.clone(), source_code.clone());
+    timings = Some(report.check_feature_with(options));
+    report.prepare_output();
+    report.duplicate_for(String::new(), String::new())
+  });
+  (original.duplicate_for(path, source_code), timings)
+}
+
+/// Reports from a running analysis, yielded as each input finishes.
+pub struct ReportStream {
+  /// Number of sources the inputs resolved to.
+  pub total: u64,
+  reports: mpsc::Receiver<Report>,
+  pipeline: JoinHandle<Profile>,
+}
+
+impl ReportStream {
+  pub async fn next(&mut self) -> Option<Report> {
+    self.reports.recv().await
   }
 
-  println!(
-    "\n{} Starting JavaScript compatibility analysis...",
-    style("🔍").bold()
-  );
+  /// Stops the pipeline if it's still running and returns its stage timings.
+  pub async fn finish(self) -> Profile {
+    drop(self.reports);
+    self.pipeline.await.unwrap_or_default()
+  }
+}
 
-  let (tx, mut rx) = mpsc::channel(32);
-  let (count_tx, mut count_rx) = mpsc::channel(32);
-  let count_tx_clone = count_tx.clone();
-  let inputs_clone = inputs.clone();
-
-  // First pass to count total files
-  let count_handle = tokio::spawn(async move {
-    let mut total = 0;
-    for input in &inputs {
-      let input_type = InputType::from_str(input);
-      match input_type {
-        InputType::File(_) => total += 1,
-        InputType::Url(_) => total += 1,
-        InputType::Directory(dir) => {
-          if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries {
-              if let Ok(entry) = entry {
-                if entry.path().extension().unwrap_or_default() == "js" {
-                  total += 1;
-                }
-              }
-            }
-          }
-        }
-        InputType::Glob(pattern) => {
-          if let Ok(paths) = glob(&pattern) {
-            for entry in paths {
-              if let Ok(path) = entry {
-                if path.extension().unwrap_or_default() == "js" {
-                  total += 1;
-                }
-              }
-            }
-          }
-        }
-      }
+/// Starts the analysis and hands back reports as they complete instead of
+/// collecting them, so embedders can render incrementally. Progress and
+/// summary output are left to the caller. Fails if BCD data can't be loaded.
+pub async fn jsco_stream(
+  inputs: Vec<String>,
+  options: Options,
+  provider: Arc<dyn SourceProvider>,
+) -> Result<ReportStream, JscoError> {
+  if cache::init() {
+    if let Some(max_age) = options.max_bcd_age {
+      bcd::expire_stale_cache(max_age)?;
     }
-    count_tx.send(total).await.ok();
-  });
+  }
+  bcd::load().await?;
 
-  // Wait for count
-  let total_files = count_rx.recv().await.unwrap_or(0);
-  let progress = ProgressBar::new(total_files);
-  progress.set_style(
-    ProgressStyle::default_bar()
-      .template(
-        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%)",
-      )
-      .unwrap()
-      .progress_chars("#>-"),
-  );
+  let mut profile = Profile::default();
+  let resolution_started = Instant::now();
+  let ignored = ignore_patterns(&options.ignore_patterns);
+  let sources = inputs
+    .iter()
+    .flat_map(|input| provider.resolve(input))
+    .filter(|source| !ignored.iter().any(|pattern| pattern.matches(source)))
+    .collect::<Vec<String>>();
+  let total = sources.len() as u64;
+  profile.add("input resolution", resolution_started.elapsed());
 
+  let (source_tx, source_rx) = mpsc::channel(32);
   let download_handle = tokio::spawn(async move {
-    for input in inputs_clone {
-      let input_type = InputType::from_str(&input);
-      match input_type {
-        InputType::File(path) => {
-          if let Ok(content) = fs::read_to_string(&path) {
-            tx.send((path.to_string_lossy().to_string(), content))
-              .await
-              .ok();
-            count_tx_clone.send(1).await.ok();
-          }
-        }
-        InputType::Url(url) => {
-          let url_with_timestamp = if url.contains('?') {
-            url.clone()
-          } else {
-            let timestamp = SystemTime::now()
-              .duration_since(UNIX_EPOCH)
-              .map(|d| d.as_millis())
-              .unwrap_or(0);
-            format!("{}?t={}", url, timestamp)
-          };
-
-          if let Ok(content) = download_with_progress(url_with_timestamp, get_cache_key(&url)).await
-          {
-            tx.send((url.to_string(), content)).await.ok();
-            count_tx_clone.send(1).await.ok();
-          }
-        }
-        InputType::Directory(dir) => {
-          println!(
-            "\n{} Scanning directory: {}",
-            style("📁").bold(),
-            style(&dir.display()).cyan()
-          );
-          let files = fs::read_dir(dir).unwrap();
-          for file in files {
-            if let Ok(file) = file {
-              let path = file.path();
-              if path.extension().unwrap_or_default() == "js" {
-                if let Ok(content) = fs::read_to_string(&path) {
-                  tx.send((path.to_string_lossy().to_string(), content))
-                    .await
-                    .ok();
-                  count_tx_clone.send(1).await.ok();
-                }
-              }
-            }
-          }
-        }
-        InputType::Glob(pattern) => {
-          println!(
-            "\n{} Scanning files matching: {}",
-            style("🔍").bold(),
-            style(&pattern).cyan()
-          );
-          if let Ok(paths) = glob(&pattern) {
-            for entry in paths {
-              if let Ok(path) = entry {
-                if path.extension().unwrap_or_default() == "js" {
-                  if let Ok(content) = fs::read_to_string(&path) {
-                    tx.send((path.to_string_lossy().to_string(), content))
-                      .await
-                      .ok();
-                    count_tx_clone.send(1).await.ok();
-                  }
-                }
-              }
-            }
-          }
-        }
+    // Time spent reading files and downloading URLs
+    let mut download_time = Duration::ZERO;
+    for source in sources {
+      let started = Instant::now();
+      let content = provider.read(&source).await;
+      download_time += started.elapsed();
+      if source_tx.send((source, content)).await.is_err() {
+        break;
       }
     }
+    download_time
   });
 
-  let mut collector = Vec::new();
-  while let Some((path, source_code)) = rx.recv().await {
-    let mut report = Report::new(path.clone(), source_code);
-    report.check_feature();
-    report.prepare_output();
-
-    if let Some(_) = count_rx.recv().await {
-      progress.inc(1);
-      let has_features = !report.found_features.is_empty();
-      let feature_count = report.found_features.len();
-      if has_features {
-        progress.println(format!(
-          "{} {} - Found {} features",
-          style("✓").green(),
-          style(&path).cyan(),
-          style(feature_count).yellow()
-        ));
+  let (report_tx, report_rx) = mpsc::channel(32);
+  let pipeline = tokio::spawn(async move {
+    let options = Arc::new(options);
+    let analyzed: Arc<Mutex<HashMap<String, Arc<OnceLock<Report>>>>> = Arc::default();
+    let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let sources = stream::unfold(source_rx, |mut source_rx| async move {
+      let source = source_rx.recv().await?;
+      Some((source, source_rx))
+    });
+    // Parsing is CPU-bound, so sources are analyzed on the blocking pool and
+    // reports are yielded in completion order
+    let mut reports = sources
+      .map(|(path, content)| {
+        let options = options.clone();
+        let analyzed = analyzed.clone();
+        let task_path = path.clone();
+        let task = tokio::task::spawn_blocking(move || match content {
+          Ok(source_code) => analyze(task_path, source_code, &options, &analyzed),
+          Err(err) => (
+            Report::failed(task_path, format!("couldn't read: {}", err)),
+            None,
+          ),
+        });
+        async move { (path, task.await) }
+      })
+      .buffer_unordered(parallelism);
+    while let Some((path, result)) = reports.next().await {
+      // A panic while analyzing one file fails that file, not the run
+      let (report, timings) = result.unwrap_or_else(|err| {
+        (
+          Report::failed(path, format!("analysis failed: {}", err)),
+          None,
+        )
+      });
+      if let Some(timings) = timings {
+        profile.add("parse", timings.parse);
+        profile.add("feature check", timings.check);
+      }
+      if report_tx.send(report).await.is_err() {
+        break;
       }
     }
+    // Dropping the stream closes the source channel and stops the download
+    drop(reports);
+    profile.add("download", download_handle.await.unwrap_or_default());
+    profile
+  });
+
+  Ok(ReportStream {
+    total,
+    reports: report_rx,
+    pipeline,
+  })
+}
+
+/// Runs the analysis reading every input through `provider`, so tests and
+/// embedders can supply sources without touching disk or network.
+pub async fn jsco_with_provider(
+  inputs: Vec<String>,
+  options: Options,
+  provider: Arc<dyn SourceProvider>,
+) -> Result<Analysis, JscoError> {
+  run(inputs, options, provider, &mut |_: &Report| {}).await
+}
+
+/// Drives `jsco_stream` to completion with progress output and the summary,
+/// handing each report to `on_report` before collecting it.
+async fn run(
+  inputs: Vec<String>,
+  options: Options,
+  provider: Arc<dyn SourceProvider>,
+  on_report: &mut (dyn FnMut(&Report) + Send),
+) -> Result<Analysis, JscoError> {
+  let quiet = options.quiet;
+  let print_profile = options.profile;
+  let max_findings = options.max_findings;
+  // Per run, so a quiet run doesn't silence the ones after it
+  set_quiet(quiet);
+  if !quiet {
+    eprintln!(
+      "\n{} Starting JavaScript compatibility analysis...",
+      style("🔍").bold()
+    );
+  }
 
+  let input_list = inputs.join(", ");
+  let mut stream = jsco_stream(inputs, options, provider).await?;
+  let total_files = stream.total;
+  if total_files == 0 {
+    // Nothing to analyze; a zero-length progress bar and summary would read
+    // like a clean run
+    eprintln!(
+      "{} No matching files found for inputs: {}",
+      style("⚠").yellow(),
+      input_list
+    );
+    stream.finish().await;
+    return Ok(Analysis::default());
+  }
+  let mut progress = Progress::new(total_files, quiet);
+  let mut collector: Vec<Report> = Vec::new();
+  let mut findings = 0;
+  let mut truncated = false;
+  while let Some(report) = stream.next().await {
+    progress.inc();
+    if let Some(error) = &report.error {
+      progress.error(format!(
+        "{} {} - {}",
+        style("✗").red(),
+        style(&report.path).cyan(),
+        error
+      ));
+    }
+    if report.has_parse_errors() {
+      progress.println(format!(
+        "{} {} - Failed to parse, not analyzed",
+        style("✗").red(),
+        style(&report.path).cyan()
+      ));
+    }
+    let feature_count = report.found_features.len();
+    if feature_count > 0 {
+      progress.println(format!(
+        "{} {} - Found {} features",
+        style("✓").green(),
+        style(&report.path).cyan(),
+        style(feature_count).yellow()
+      ));
+    }
+    on_report(&report);
     collector.push(report);
+
+    findings += feature_count;
+    if max_findings.is_some_and(|max| findings >= max) {
+      truncated = true;
+      break;
+    }
+  }
+
+  progress.finish();
+  // Dropping the stream cancels whatever is still being read or analyzed
+  let profile = stream.finish().await;
+  // Reports arrive in completion order
+  collector.sort_by(|a, b| a.path.cmp(&b.path));
+  if truncated {
+    eprintln!(
+      "{} Stopped after {} features in {} of {} files; results are truncated",
+      style("⚠").yellow(),
+      findings,
+      collector.len(),
+      total_files
+    );
+  }
+  if print_profile {
+    profile.print();
   }
 
-  progress.finish_with_message("Analysis complete!");
-  count_handle.await.ok();
-  download_handle.await.ok();
+  let analysis = Analysis {
+    reports: collector,
+    truncated,
+  };
+  if quiet {
+    return Ok(analysis);
+  }
 
-  println!("\n{} Analysis Summary:", style("📊").bold());
-  println!("  {} Total files processed", style(total_files).cyan());
-  println!(
+  eprintln!("\n{} Analysis Summary:", style("📊").bold());
+  eprintln!("  {} Total files processed", style(total_files).cyan());
+  eprintln!(
     "  {} Files with features",
     style(
-      collector
+      analysis
+        .reports
         .iter()
         .filter(|r| !r.found_features.is_empty())
         .count()
     )
     .green()
   );
-  println!(
+  eprintln!(
     "  {} Total features found",
     style(
-      collector
+      analysis
+        .reports
         .iter()
         .map(|r| r.found_features.len())
         .sum::<usize>()
     )
     .yellow()
   );
-  println!("");
+  eeprintln!();
 
-  collector
+  Ok(analysis)
 }
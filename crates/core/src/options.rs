@@ -0,0 +1,80 @@
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use crate::feature::JsFeature;
+
+/// Knobs for a single `jsco` run. `Options::default()` matches the behavior
+/// of the plain `jsco()` entry point.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+  /// Suppress progress and summary output entirely.
+  pub quiet: bool,
+  /// Opt-in features to detect in addition to the defaults.
+  pub enabled: HashSet<JsFeature>,
+  /// Report the ES year each method chain requires.
+  pub chain_analysis: bool,
+  /// Print per-stage timings to stderr.
+  pub profile: bool,
+  /// Refetch cached BCD data older than this.
+  pub max_bcd_age: Option<Duration>,
+  /// Extra method-call detectors supplied by the user.
+  pub custom_methods: Vec<CustomMethod>,
+  /// Suggest `??` for `a || default` fallbacks.
+  pub nullish_advisory: bool,
+  /// Stop once this many features have been found, skipping remaining inputs.
+  pub max_findings: Option<usize>,
+  /// Globs of resolved sources to skip, e.g. `**/*.min.js`.
+  pub ignore_patterns: Vec<String>,
+  /// Extensions to scan directories and globs for; empty means `js`, `mjs`,
+  /// `cjs` and `jsx`.
+  pub extensions: Vec<String>,
+  /// Refetch URL inputs even when a cached copy exists.
+  pub refresh: bool,
+  /// Parse every source as a script or a module instead of going by its
+  /// extension.
+  pub source_kind: Option<SourceKind>,
+  /// Look up `"type"` in the nearest package.json to tell whether a `.js`
+  /// file is a module. Reads from disk relative to the working directory, so
+  /// only runs over real files set it.
+  pub read_package_json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+  Script,
+  Module,
+}
+
+impl FromStr for SourceKind {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "script" => Ok(SourceKind::Script),
+      "module" => Ok(SourceKind::Module),
+      _ => Err(format!("unknown source type: {}", s)),
+    }
+  }
+}
+
+/// Reports every `.method(...)` call under a BCD key of the user's choosing,
+/// e.g. `reduceRight` as `javascript.builtins.Array.reduceRight`.
+#[derive(Debug, Clone)]
+pub struct CustomMethod {
+  pub method: String,
+  pub feature: JsFeature,
+}
+
+impl CustomMethod {
+  pub fn new(method: impl Into<String>, key: &str) -> Self {
+    Self {
+      method: method.into(),
+      feature: JsFeature::custom(key),
+    }
+  }
+}
+
+impl Options {
+  pub fn is_enabled(&self, feature: JsFeature) -> bool {
+    !feature.is_opt_in() || self.enabled.contains(&feature)
+  }
+}
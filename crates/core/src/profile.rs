@@ -0,0 +1,48 @@
+use console::style;
+use std::{fmt, time::Duration};
+
+/// Wall-clock time per pipeline stage, aggregated across files, for
+/// `--profile`. Printed to stderr so it never mixes with report output.
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+  stages: Vec<(&'static str, Duration, usize)>,
+}
+
+impl Profile {
+  pub fn add(&mut self, stage: &'static str, elapsed: Duration) {
+    if let Some((_, total, count)) = self.stages.iter_mut().find(|(name, ..)| *name == stage) {
+      *total += elapsed;
+      *count += 1;
+    } else {
+      self.stages.push((stage, elapsed, 1));
+    }
+  }
+
+  /// How many times `stage` was timed, e.g. the number of files parsed.
+  pub fn count(&self, stage: &str) -> usize {
+    self
+      .stages
+      .iter()
+      .find(|(name, ..)| *name == stage)
+      .map_or(0, |(.., count)| *count)
+  }
+
+  pub fn print(&self) {
+    eprint!("{}", self);
+  }
+}
+
+impl fmt::Display for Profile {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (stage, elapsed, _) in &self.stages {
+      writeln!(
+        f,
+        "{} {:<18} {:>10.2?}",
+        style("[profile]").dim(),
+        stage,
+        elapsed
+      )?;
+    }
+    Ok(())
+  }
+}
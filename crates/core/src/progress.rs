@@ -0,0 +1,135 @@
+use console::Term;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{
+  fmt::Display,
+  sync::atomic::{AtomicBool, Ordering},
+};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silences status messages printed outside a run's progress, such as BCD
+/// cache hits and downloads. They go to stderr either way, so stdout stays
+/// clean for machine-readable formats.
+pub fn set_quiet(quiet: bool) {
+  QUIET.store(quiet, Ordering::Relaxed);
+}
+
+fn is_quiet() -> bool {
+  QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints a one-line status message to stderr unless quiet.
+pub(crate) fn status(message: impl Display) {
+  if !is_quiet() {
+    eprintln!("{}", message);
+  }
+}
+
+/// Whether in-place progress (`\r` redraws) may be drawn on stderr.
+pub(crate) fn draws_inline() -> bool {
+  !is_quiet() && Term::stderr().is_term()
+}
+
+/// Progress reporting for a run, on stderr. Renders the fancy bar only when
+/// stderr is an interactive terminal; CI logs get periodic plain lines instead.
+pub(crate) enum Progress {
+  Bar(ProgressBar),
+  Plain { total: u64, done: u64 },
+  Quiet,
+}
+
+impl Progress {
+  pub fn new(total: u64, quiet: bool) -> Self {
+    Self::for_terminal(total, quiet, Term::stderr().is_term())
+  }
+
+  fn for_terminal(total: u64, quiet: bool, is_term: bool) -> Self {
+    if quiet {
+      return Self::Quiet;
+    }
+    if !is_term {
+      return Self::Plain { total, done: 0 };
+    }
+
+    let progress = ProgressBar::new(total);
+    progress.set_style(
+      ProgressStyle::default_bar()
+        .template(
+          "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%)",
+        )
+        .unwrap()
+        .progress_chars("#>-"),
+    );
+    Self::Bar(progress)
+  }
+
+  pub fn inc(&mut self) {
+    match self {
+      Self::Bar(progress) => progress.inc(1),
+      Self::Plain { total, done } => {
+        *done += 1;
+        if let Some(line) = plain_line(*total, *done) {
+          eprintln!("{}", line);
+        }
+      }
+      Self::Quiet => {}
+    }
+  }
+
+  pub fn println(&self, message: String) {
+    match self {
+      Self::Bar(progress) => progress.println(message),
+      Self::Plain { .. } => eprintln!("{}", message),
+      Self::Quiet => {}
+    }
+  }
+
+  /// Like `println`, but on stderr and shown even when quiet.
+  pub fn error(&self, message: String) {
+    match self {
+      Self::Bar(progress) => progress.suspend(|| eprintln!("{}", message)),
+      Self::Plain { .. } | Self::Quiet => eprintln!("{}", message),
+    }
+  }
+
+  pub fn finish(&self) {
+    if let Self::Bar(progress) = self {
+      progress.finish_with_message("Analysis complete!");
+    }
+  }
+}
+
+/// The plain log line after `done` of `total` files, if one is due. Logs
+/// roughly every 10% so large runs don't flood the output.
+fn plain_line(total: u64, done: u64) -> Option<String> {
+  let step = (total / 10).max(1);
+  (done % step == 0 || done == total).then(|| format!("Processed {}/{} files", done, total))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn non_terminals_get_plain_lines_instead_of_a_spinner() {
+    assert!(matches!(
+      Progress::for_terminal(25, false, false),
+      Progress::Plain { .. }
+    ));
+    assert!(matches!(
+      Progress::for_terminal(25, true, false),
+      Progress::Quiet
+    ));
+
+    let lines = (1..=25)
+      .filter_map(|done| plain_line(25, done))
+      .collect::<Vec<_>>();
+    assert_eq!(lines.first().unwrap(), "Processed 2/25 files");
+    assert_eq!(lines.last().unwrap(), "Processed 25/25 files");
+    assert_eq!(lines.len(), 13);
+    // No redraws or spinner frames, just one line per update
+    assert!(lines
+      .iter()
+      .all(|line| line.chars().all(|c| c.is_ascii_graphic() || c == ' ')));
+  }
+}
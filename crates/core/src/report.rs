@@ -1,33 +1,168 @@
 use oxc::allocator::Allocator;
-use oxc::ast::ast::ArrayExpressionElement;
+use oxc::ast::ast::ArrayPattern;
+use oxc::ast::ast::AssignmentOperator;
+use oxc::ast::ast::AssignmentTarget;
+use oxc::ast::ast::BinaryExpression;
+use oxc::ast::ast::BinaryOperator;
+use oxc::ast::ast::BindingPattern;
+use oxc::ast::ast::BindingPatternKind;
+use oxc::ast::ast::CallExpression;
+use oxc::ast::ast::Class;
+use oxc::ast::ast::ClassElement;
 use oxc::ast::ast::Expression;
+use oxc::ast::ast::IdentifierReference;
 use oxc::ast::ast::MemberExpression;
+use oxc::ast::ast::NewExpression;
+use oxc::ast::ast::ObjectPattern;
+use oxc::ast::ast::ObjectPropertyKind;
+use oxc::ast::ast::Program;
+use oxc::ast::ast::PropertyKey;
+use oxc::ast::ast::RegExpFlags;
+use oxc::ast::ast::Statement;
+use oxc::ast::ast::UnaryOperator;
 use oxc::ast::AstKind;
 use oxc::diagnostics::OxcDiagnostic;
 use oxc::parser::Parser;
+use oxc::span::GetSpan;
 use oxc::span::SourceType;
 use oxc::span::Span;
-use oxc_semantic::SemanticBuilder;
-use serde::Serialize;
-use std::collections::HashMap;
+use oxc_semantic::{AstNode, Semantic, SemanticBuilder};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::feature::{BrowserSupport, FeatureReport, JsFeature, JsFeatureTrait};
+use crate::diagnostic::Diagnostic;
+use crate::feature::{
+  array_method_es_year, deserialize_shared, es_year_label, BrowserSupport, FeatureReport,
+  JsFeature, JsFeatureTrait, LineIndex, Location,
+};
+use crate::options::{Options, SourceKind};
+use crate::version::raise_required;
 
-#[derive(Debug, Clone, Serialize)]
+/// Globals whose direct use ties code to one environment.
+const ENVIRONMENT_GLOBALS: [&str; 4] = ["window", "self", "global", "process"];
+
+/// Findings for one source. Deserializing reads back the JSON output, with
+/// `features` left empty; `found_features` holds the findings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Report {
-  #[serde(serialize_with = "serialize_browser_support")]
+  #[serde(
+    serialize_with = "serialize_browser_support",
+    deserialize_with = "deserialize_shared"
+  )]
   pub browser_support: Arc<Mutex<BrowserSupport>>,
   #[serde(skip)]
   pub features: Arc<Mutex<HashMap<JsFeature, FeatureReport>>>,
-  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub found_features: Vec<FeatureReport>,
+  #[serde(
+    default,
+    serialize_with = "serialize_diagnostics",
+    deserialize_with = "deserialize_shared",
+    skip_serializing_if = "has_no_diagnostics"
+  )]
+  pub diagnostics: Arc<Mutex<Vec<Diagnostic>>>,
+  /// Why the source couldn't be parsed. A report with parse errors has no
+  /// findings because it was never analyzed, not because it's clean.
+  #[serde(
+    default,
+    serialize_with = "serialize_parse_errors",
+    deserialize_with = "deserialize_shared",
+    skip_serializing_if = "has_no_parse_errors"
+  )]
+  pub parse_errors: Arc<Mutex<Vec<String>>>,
+  /// Per browser, the first version supporting every feature found. Set by
+  /// `prepare_output`.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub required_versions: BrowserSupport,
+  /// Why the source couldn't be read or analyzed at all.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
   pub path: String,
   pub source_code: String,
 }
 
 pub type Reports = Vec<Report>;
 
+/// Combines the reports of separate runs, e.g. one per package of a monorepo,
+/// keeping the first report for each path.
+pub fn merge_reports(runs: Vec<Reports>) -> Reports {
+  let mut paths = HashSet::new();
+  runs
+    .into_iter()
+    .flatten()
+    .filter(|report| paths.insert(report.path.clone()))
+    .collect()
+}
+
+/// Per browser, the oldest version that runs every file in `reports`.
+pub fn min_required_versions(reports: &Reports) -> BrowserSupport {
+  let mut required = BrowserSupport::default();
+  for report in reports {
+    raise_required(&mut required, &report.min_required_versions());
+  }
+  required
+}
+
+/// Where one file uses a feature.
+#[derive(Debug, Serialize)]
+pub struct FeatureUsage<'a> {
+  pub path: &'a str,
+  pub locations: &'a [Location],
+}
+
+/// Every use of one feature across a run, answering "which files use this?"
+/// instead of "what does this file use?".
+#[derive(Debug, Serialize)]
+pub struct FeatureGroup<'a> {
+  pub feature: JsFeature,
+  pub mdn_url: &'a str,
+  pub deprecated: bool,
+  /// Browser name -> first supporting version, the same for every file.
+  pub support: BrowserSupport,
+  pub files: Vec<FeatureUsage<'a>>,
+}
+
+/// Pivots per-file reports into one group per feature, ordered by BCD key.
+pub fn group_by_feature(reports: &Reports) -> Vec<FeatureGroup<'_>> {
+  let mut groups: BTreeMap<&str, FeatureGroup> = BTreeMap::new();
+  for report in reports {
+    for feature in &report.found_features {
+      groups
+        .entry(feature.feat_type.key())
+        .or_insert_with(|| FeatureGroup {
+          feature: feature.feat_type,
+          mdn_url: &feature.mdn_url,
+          deprecated: feature.deprecated,
+          support: feature.support.lock().unwrap().clone(),
+          files: Vec::new(),
+        })
+        .files
+        .push(FeatureUsage {
+          path: &report.path,
+          locations: &feature.locations,
+        });
+    }
+  }
+  groups.into_values().collect()
+}
+
+thread_local! {
+  static PARSE_ARENA: RefCell<Allocator> = RefCell::new(Allocator::default());
+}
+
+/// Wall-clock time spent in each stage of `check_feature_with`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CheckTimings {
+  pub parse: Duration,
+  pub check: Duration,
+}
+
 fn serialize_browser_support<S>(
   browser_support: &Arc<Mutex<BrowserSupport>>,
   serializer: S,
@@ -38,50 +173,232 @@ where
   browser_support.lock().unwrap().serialize(serializer)
 }
 
+fn serialize_diagnostics<S>(
+  diagnostics: &Arc<Mutex<Vec<Diagnostic>>>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  diagnostics.lock().unwrap().serialize(serializer)
+}
+
+fn has_no_diagnostics(diagnostics: &Arc<Mutex<Vec<Diagnostic>>>) -> bool {
+  diagnostics.lock().unwrap().is_empty()
+}
+
+fn serialize_parse_errors<S>(
+  parse_errors: &Arc<Mutex<Vec<String>>>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  parse_errors.lock().unwrap().serialize(serializer)
+}
+
+fn has_no_parse_errors(parse_errors: &Arc<Mutex<Vec<String>>>) -> bool {
+  parse_errors.lock().unwrap().is_empty()
+}
+
 impl Report {
   pub fn new(path: String, source_code: String) -> Self {
     Self {
       features: Arc::new(Mutex::new(HashMap::new())),
       browser_support: Arc::new(Mutex::new(BrowserSupport::default())),
       found_features: Vec::new(),
+      diagnostics: Arc::new(Mutex::new(Vec::new())),
+      parse_errors: Arc::new(Mutex::new(Vec::new())),
+      required_versions: BrowserSupport::default(),
+      error: None,
+      path,
+      source_code,
+    }
+  }
+
+  /// A report for a source that couldn't be read or analyzed.
+  pub fn failed(path: String, error: String) -> Self {
+    Self {
+      error: Some(error),
+      ..Self::new(path, String::new())
+    }
+  }
+
+  /// A copy of this report's findings for another input with identical
+  /// source, so duplicates don't have to be parsed again.
+  pub fn duplicate_for(&self, path: String, source_code: String) -> Report {
+    Report {
+      browser_support: Arc::new(Mutex::new(self.browser_support.lock().unwrap().clone())),
+      features: Arc::new(Mutex::new(self.features.lock().unwrap().clone())),
+      found_features: self.found_features.clone(),
+      diagnostics: Arc::new(Mutex::new(self.diagnostics.lock().unwrap().clone())),
+      parse_errors: Arc::new(Mutex::new(self.parse_errors.lock().unwrap().clone())),
+      required_versions: self.required_versions.clone(),
+      error: self.error.clone(),
       path,
       source_code,
     }
   }
 
+  /// Drops findings and diagnostics whose span doesn't intersect the byte
+  /// range `start..end`. Call before `prepare_output`.
+  pub fn retain_range(&self, start: usize, end: usize) {
+    let intersects = |span: &Span| (span.start as usize) < end && start < span.end as usize;
+    let mut features = self.features.lock().unwrap();
+    for feature in features.values_mut() {
+      feature.retain_spans(intersects);
+    }
+    features.retain(|_, feature| !feature.found_in.is_empty());
+    self
+      .diagnostics
+      .lock()
+      .unwrap()
+      .retain(|diagnostic| intersects(&diagnostic.span));
+  }
+
+  /// No findings, no diagnostics and read and parsed successfully.
+  pub fn is_clean(&self) -> bool {
+    self.found_features.is_empty()
+      && self.diagnostics.lock().unwrap().is_empty()
+      && !self.has_parse_errors()
+      && self.error.is_none()
+  }
+
+  pub fn has_parse_errors(&self) -> bool {
+    !self.parse_errors.lock().unwrap().is_empty()
+  }
+
   pub fn prepare_output(&mut self) {
     self.found_features = self.features.lock().unwrap().values().cloned().collect();
+    let lines = LineIndex::new(&self.source_code);
     for feature in &mut self.found_features {
-      feature.prepare_output(&self.source_code);
+      feature.prepare_output(&self.source_code, &lines);
     }
+    for diagnostic in self.diagnostics.lock().unwrap().iter_mut() {
+      diagnostic.prepare_output(&self.source_code, &lines);
+    }
+    self.required_versions = self.min_required_versions();
+  }
+
+  /// Per browser, the most demanding `version_added` among the found
+  /// features: the oldest version that runs the whole file.
+  pub fn min_required_versions(&self) -> BrowserSupport {
+    let mut required = BrowserSupport::default();
+    for feature in &self.found_features {
+      raise_required(&mut required, &feature.support.lock().unwrap());
+    }
+    required
   }
 
   pub fn check_feature(&self) {
-    let allocator = Allocator::default();
-    let source_type = SourceType::from_path(&self.path).unwrap();
-    let ret = Parser::new(&allocator, &self.source_code, source_type).parse();
+    self.check_feature_with(&Options::default());
+  }
+
+  /// Parses into this thread's arena, which is reset and reused for each file
+  /// instead of allocating a fresh one.
+  pub fn check_feature_with(&self, options: &Options) -> CheckTimings {
+    PARSE_ARENA.with(|arena| {
+      let mut allocator = arena.borrow_mut();
+      allocator.reset();
+      self.check_feature_in(&allocator, options)
+    })
+  }
+
+  /// Like `check_feature_with`, parsing into a caller-owned arena. The AST
+  /// borrows from `allocator`, so it must outlive this call only.
+  pub fn check_feature_in(&self, allocator: &Allocator, options: &Options) -> CheckTimings {
+    let mut timings = CheckTimings::default();
+    let started = Instant::now();
+    // oxc parses decorators for every source type, so `@sealed class Foo {}`
+    // in a plain .js file doesn't need extra parser options. JSX is enabled
+    // for .js and .jsx alike; unknown or missing extensions (a URL like
+    // `/bundle`) are parsed as modules
+    let mut source_type = SourceType::from_path(&self.path).unwrap_or_else(|_| SourceType::mjs());
+    if let Some(kind) = options.source_kind {
+      source_type = source_type.with_module(kind == SourceKind::Module);
+    }
+    let ret = Parser::new(allocator, &self.source_code, source_type).parse();
+    timings.parse = started.elapsed();
 
     if !ret.errors.is_empty() {
-      println!("Failed to parse JavaScript code");
+      let mut parse_errors = self.parse_errors.lock().unwrap();
       for error in ret.errors {
-        println!("Error: {}", error);
+        parse_errors.push(error.to_string());
+        if let Some(diagnostic) = mixed_coalesce_diagnostic(&error) {
+          self.add_diagnostic(diagnostic);
+        }
+        if let Some(diagnostic) = await_outside_async_diagnostic(&error) {
+          self.add_diagnostic(diagnostic);
+        }
       }
-      return;
+      return timings;
+    }
+
+    if let Some(diagnostic) =
+      module_syntax_diagnostic(&self.path, &ret.program, options.read_package_json)
+    {
+      self.add_diagnostic(diagnostic);
     }
 
+    let started = Instant::now();
     let semantic_ret = SemanticBuilder::new().build(&ret.program);
-    let errors: Vec<OxcDiagnostic> = vec![];
+    let semantic = semantic_ret.semantic;
 
-    for node in semantic_ret.semantic.nodes() {
+    for node in semantic.nodes() {
       match node.kind() {
         AstKind::LogicalExpression(it) if it.operator.as_str() == "??" => {
           self.process_found(JsFeature::NullishCoalescing, it.span);
         }
+        AstKind::LogicalExpression(it)
+          if options.nullish_advisory
+            && it.operator.as_str() == "||"
+            && is_default_value(&it.right) =>
+        {
+          self.add_diagnostic(Diagnostic::new(
+            "prefer-nullish-coalescing",
+            "`||` also replaces 0, \"\" and false; use `??` if only null and undefined should fall back",
+            it.span,
+          ));
+        }
+        // `typeof a?.b` and `delete a?.b` wrap the chain in a UnaryExpression,
+        // so `it.span` covers only `a?.b`, never the operator.
         AstKind::ChainExpression(it) => {
           self.process_found(JsFeature::OptionalChaining, it.span);
         }
+        // Reported on the head (`async function f`, `async (`) rather than
+        // the whole body, even when nothing is awaited
+        AstKind::Function(it) if it.r#async || it.generator => {
+          let feature = match (it.r#async, it.generator) {
+            (true, true) => JsFeature::AsyncGenerator,
+            (true, false) => JsFeature::AsyncFunction,
+            _ => JsFeature::Generator,
+          };
+          self.process_found(feature, head_span(it.span, it.params.span));
+        }
+        AstKind::ArrowFunctionExpression(it) if it.r#async => {
+          self.process_found(JsFeature::AsyncFunction, head_span(it.span, it.params.span));
+        }
+        AstKind::YieldExpression(it) => {
+          let feature = if enclosing_function_async(&semantic, node) == Some(true) {
+            JsFeature::AsyncGenerator
+          } else {
+            JsFeature::Generator
+          };
+          self.process_found(feature, it.span);
+        }
+        AstKind::Decorator(it) => {
+          self.process_found(JsFeature::Decorator, it.span);
+        }
+        // Both `class A {}` and `const B = class {}`, on the head like
+        // functions
+        AstKind::Class(it) => {
+          self.process_found(JsFeature::ClassSyntax, class_head_span(it));
+        }
         AstKind::ClassBody(it) => {
           for prop in it.body.iter() {
+            if let ClassElement::StaticBlock(block) = prop {
+              self.process_found(JsFeature::ClassStaticBlock, block.span);
+            }
             if let Some(key) = prop.property_key() {
               match key {
                 oxc::ast::ast::PropertyKey::PrivateIdentifier(ident) => {
@@ -96,23 +413,97 @@ impl Report {
             }
           }
         }
+        // `await` in a function that isn't async never gets here: it's a
+        // parse error, see `await_outside_async_diagnostic`
         AstKind::AwaitExpression(it) => {
-          self.process_found(JsFeature::Await, it.span);
-        }
-        AstKind::AssignmentExpression(it) => match it.operator.as_str() {
-          "&&=" | "||=" | "??=" => {
-            self.process_found(JsFeature::LogicalAssignment, it.span);
+          if enclosing_function_async(&semantic, node).is_none() {
+            self.process_found(JsFeature::TopLevelAwait, it.span);
+          } else {
+            self.process_found(JsFeature::Await, it.span);
+          }
+          if is_non_thenable(&it.argument) {
+            self.add_diagnostic(Diagnostic::new(
+              "await-non-thenable",
+              "`await` on a value that can never be a promise; this is usually a mistake",
+              it.span,
+            ));
           }
-          _ => {}
-        },
+        }
+        // The expression span runs from the target through the value, so the
+        // snippet shows `x ||= y` rather than just the operator. Only the
+        // operator is checked, so `obj.x ??= y` and `arr[i] ||= z` count too
+        AstKind::AssignmentExpression(it) if it.operator.is_logical() => {
+          self.process_found(JsFeature::LogicalAssignment, it.span);
+        }
+        AstKind::AssignmentExpression(it) if it.operator == AssignmentOperator::Exponential => {
+          self.process_found(JsFeature::ExponentiationAssignment, it.span);
+        }
+        AstKind::BinaryExpression(it) if it.operator == BinaryOperator::Exponential => {
+          self.process_found(JsFeature::Exponentiation, it.span);
+        }
         AstKind::NumericLiteral(it) => {
           if it.value.to_string().contains('_') {
             self.process_found(JsFeature::NumericSeparator, it.span);
           }
         }
+        // One finding per flag, so `/./su` shows up under both features
+        AstKind::RegExpLiteral(it) => {
+          if it.regex.flags.contains(RegExpFlags::S) {
+            self.process_found(JsFeature::RegExpDotAll, it.span);
+          }
+          if it.regex.flags.contains(RegExpFlags::U) {
+            self.process_found(JsFeature::RegExpUnicode, it.span);
+          }
+        }
         AstKind::ImportExpression(it) => {
           self.process_found(JsFeature::DynamicImport, it.span);
         }
+        AstKind::ObjectPattern(it) if is_outer_pattern(&semantic, node) => {
+          let mut shape = PatternShape::default();
+          shape.visit_object(it, 0);
+          self.process_found_with_detail(JsFeature::Destructuring, it.span, shape.detail());
+        }
+        AstKind::ArrayPattern(it) if is_outer_pattern(&semantic, node) => {
+          let mut shape = PatternShape::default();
+          shape.visit_array(it, 0);
+          self.process_found_with_detail(JsFeature::Destructuring, it.span, shape.detail());
+        }
+        // `[a, b] = [b, a]` and `({ a } = obj)`, as opposed to declarations
+        AstKind::AssignmentExpression(it)
+          if matches!(
+            it.left,
+            AssignmentTarget::ArrayAssignmentTarget(_)
+              | AssignmentTarget::ObjectAssignmentTarget(_)
+          ) =>
+        {
+          self.process_found(JsFeature::Destructuring, it.left.span());
+        }
+        // Array rest (`[x, ...rest]`) is plain ES2015 destructuring, but object
+        // rest (`{ a, ...rest }`) only arrived with object spread in ES2018
+        AstKind::BindingRestElement(it)
+          if matches!(
+            semantic.nodes().parent_kind(node.id()),
+            Some(AstKind::ObjectPattern(_))
+          ) =>
+        {
+          self.process_found(JsFeature::RestSpread, it.span);
+        }
+        AstKind::MethodDefinition(it) if it.computed => {
+          if let Some(feature) = iterator_method_feature(&semantic, &it.key) {
+            self.process_found(feature, it.span);
+          }
+        }
+        AstKind::ObjectProperty(it) if it.computed => {
+          if let Some(feature) = iterator_method_feature(&semantic, &it.key) {
+            self.process_found(feature, it.span);
+          }
+        }
+        AstKind::BigIntLiteral(it) => {
+          self.process_found(JsFeature::BigInt, it.span);
+        }
+        AstKind::BinaryExpression(it) if is_typeof_bigint_check(it) => {
+          self.process_found(JsFeature::BigInt, it.span);
+        }
         AstKind::CatchClause(it) => {
           if it.param.is_none() {
             self.process_found(JsFeature::OptionalCatchBinding, it.span);
@@ -121,28 +512,17 @@ impl Report {
         AstKind::ForOfStatement(it) => {
           if matches!(it.r#await, true) {
             self.process_found(JsFeature::AsyncIteration, it.span);
+            // A module-level `for await` awaits outside any function
+            if enclosing_function_async(&semantic, node).is_none() {
+              self.process_found(JsFeature::TopLevelAwait, it.span);
+            }
           }
         }
+        // Spreads in arrays, calls and object literals are all `SpreadElement`
+        // nodes, so each `...x` is reported once with its own span
         AstKind::SpreadElement(it) => {
           self.process_found(JsFeature::RestSpread, it.span);
         }
-        AstKind::ObjectExpression(obj) => {
-          for prop in &obj.properties {
-            if prop.is_spread() {
-              self.process_found(JsFeature::RestSpread, obj.span);
-            }
-          }
-        }
-        AstKind::ArrayExpression(arr) => {
-          for elem in &arr.elements {
-            match elem {
-              ArrayExpressionElement::SpreadElement(spread) => {
-                self.process_found(JsFeature::RestSpread, spread.span);
-              }
-              _ => {}
-            }
-          }
-        }
         // ServiceWorker
         AstKind::MemberExpression(expr) => {
           if let MemberExpression::StaticMemberExpression(static_expr) = expr {
@@ -162,6 +542,13 @@ impl Report {
                 }
               }
             }
+            if let Expression::Identifier(obj) = &static_expr.object {
+              if let Some(feature) = static_member_feature(&obj.name, &static_expr.property.name) {
+                if options.is_enabled(feature) && is_global(&semantic, obj) {
+                  self.process_found(feature, static_expr.span);
+                }
+              }
+            }
           }
         }
         // requestIdleCallback
@@ -173,17 +560,99 @@ impl Report {
           {
             self.process_found(JsFeature::RequestIdleCallback, expr.span);
           }
+          let is_chain_end = !matches!(
+            semantic.nodes().parent_kind(node.id()),
+            Some(AstKind::MemberExpression(_))
+          );
+          if options.chain_analysis && is_chain_end {
+            self.check_method_chain(expr);
+          }
+          if let Some((object, property)) = global_static_call(&semantic, expr) {
+            if let Some(feature) = static_call_feature(object, property) {
+              if options.is_enabled(feature) {
+                self.process_found(feature, expr.span);
+              }
+            }
+          }
+          if let Some(name) = global_call(&semantic, expr) {
+            if let Some(feature) = global_call_feature(name) {
+              if options.is_enabled(feature) {
+                self.process_found(feature, expr.span);
+              }
+            }
+          }
+          if let Expression::StaticMemberExpression(member) = &expr.callee {
+            // Heuristic: any `.finally(...)` call is assumed to be on a Promise
+            if member.property.name == "finally" {
+              self.process_found(JsFeature::PromiseFinally, expr.span);
+            }
+            // Likewise `.subarray(...)` only exists on typed arrays
+            if member.property.name == "subarray" {
+              self.process_found(JsFeature::TypedArray, expr.span);
+            }
+            if let Some(feature) = array_method_feature(&member.property.name) {
+              self.process_found(feature, expr.span);
+            }
+            if member.property.name == "sort" && options.is_enabled(JsFeature::ArraySortStability) {
+              self.process_found_with_detail(
+                JsFeature::ArraySortStability,
+                expr.span,
+                Some("sort is only guaranteed to be stable since ES2019".to_string()),
+              );
+            }
+            for custom in &options.custom_methods {
+              if member.property.name == custom.method {
+                self.process_found(custom.feature, expr.span);
+              }
+            }
+            if let Some(feature) = intl_constructor(&semantic, &member.object)
+              .and_then(|constructor| intl_method_feature(constructor, &member.property.name))
+            {
+              self.process_found(feature, expr.span);
+            }
+          }
+        }
+        AstKind::IdentifierReference(ident)
+          if options.is_enabled(JsFeature::EnvironmentGlobal)
+            && ENVIRONMENT_GLOBALS.contains(&ident.name.as_str())
+            && is_global(&semantic, ident) =>
+        {
+          self.process_found_with_detail(
+            JsFeature::EnvironmentGlobal,
+            ident.span,
+            Some(ident.name.to_string()),
+          );
+        }
+        // `Float64Array.from(...)`, `x instanceof Int8Array` and the like
+        AstKind::IdentifierReference(ident)
+          if typed_array_feature(&ident.name).is_some()
+            && is_global(&semantic, ident)
+            && !is_new_callee(&semantic, node, ident) =>
+        {
+          if let Some(feature) = typed_array_feature(&ident.name) {
+            self.process_found(feature, ident.span);
+          }
+        }
+        AstKind::NewExpression(expr) => {
+          if let Some(name) = global_constructor(&semantic, expr) {
+            if let Some(feature) = constructor_feature(name) {
+              if options.is_enabled(feature) {
+                self.process_found(feature, expr.span);
+              }
+            }
+          }
+          for (feature, option) in unified_number_format_options(&semantic, expr) {
+            if options.is_enabled(feature) {
+              self.process_found_with_detail(feature, expr.span, Some(option));
+            }
+          }
         }
         _ => {}
       }
     }
 
-    if !errors.is_empty() {
-      println!("Failed to parse JavaScript code");
-      for error in errors {
-        println!("Error: {}", error);
-      }
-    }
+    timings.check = started.elapsed();
+    timings
   }
 
   pub fn get_features(mut self) -> Vec<FeatureReport> {
@@ -191,7 +660,44 @@ impl Report {
     self.found_features.clone()
   }
 
+  /// Reports the ES year a method chain like `a.flatMap(f).at(-1)` needs,
+  /// taking the newest method in the chain. Chains that stay within ES5 are
+  /// not reported.
+  fn check_method_chain(&self, expr: &CallExpression) {
+    let methods = method_chain(expr);
+    if methods.len() < 2 {
+      return;
+    }
+    let Some(floor) = methods.iter().filter_map(|m| array_method_es_year(m)).max() else {
+      return;
+    };
+    if floor <= 2009 {
+      return;
+    }
+
+    let label = es_year_label(floor);
+    let mut diagnostic = Diagnostic::new(
+      "method-chain",
+      format!(
+        "Method chain `{}()` requires {}",
+        methods.join("()."),
+        label
+      ),
+      expr.span,
+    );
+    diagnostic.location.detail = Some(label);
+    self.add_diagnostic(diagnostic);
+  }
+
+  fn add_diagnostic(&self, diagnostic: Diagnostic) {
+    self.diagnostics.lock().unwrap().push(diagnostic);
+  }
+
   fn process_found(&self, feature: JsFeature, span: Span) {
+    self.process_found_with_detail(feature, span, None);
+  }
+
+  fn process_found_with_detail(&self, feature: JsFeature, span: Span, detail: Option<String>) {
     let mut features = self.features.lock().unwrap();
     let browser_support = feature.browser_support();
     self
@@ -200,15 +706,525 @@ impl Report {
       .unwrap()
       .extend(browser_support.clone());
     if let Some(report) = features.get_mut(&feature) {
-      report.add_span(span);
-      report.support.lock().unwrap().extend(browser_support);
-      report.mdn_url = feature.mdn_url();
+      report.add_span_with_detail(span, detail);
     } else {
-      let mut report = FeatureReport::new(feature, self.browser_support.lock().unwrap().clone());
-      report.add_span(span);
-      report.support.lock().unwrap().extend(browser_support);
+      // Each feature carries only its own support data, whatever else the
+      // file uses
+      let mut report = FeatureReport::new(feature, browser_support);
+      report.add_span_with_detail(span, detail);
       report.mdn_url = feature.mdn_url();
+      report.deprecated = feature.is_deprecated();
+      report.experimental = feature.is_experimental();
       features.insert(feature, report);
     }
   }
 }
+
+/// `a ?? b || c` is a syntax error. oxc reports it generically, so translate
+/// it into a message pointing at the fix for people migrating `||` to `??`.
+fn mixed_coalesce_diagnostic(error: &OxcDiagnostic) -> Option<Diagnostic> {
+  if !error
+    .message
+    .contains("coalesce expressions cannot be mixed")
+  {
+    return None;
+  }
+  Some(Diagnostic::new(
+    "mixed-nullish-logical",
+    "`??` can't be mixed with `||` or `&&` without parentheses; write `(a ?? b) || c` or `a ?? (b || c)`",
+    error_span(error),
+  ))
+}
+
+/// `await` in a function that isn't `async` is a syntax error too; name it
+/// plainly for people converting callbacks to async code.
+fn await_outside_async_diagnostic(error: &OxcDiagnostic) -> Option<Diagnostic> {
+  if !error
+    .message
+    .contains("`await` is only allowed within async functions")
+  {
+    return None;
+  }
+  Some(Diagnostic::new(
+    "await-outside-async",
+    "`await` used inside a function that isn't `async`",
+    error_span(error),
+  ))
+}
+
+/// Span of a parser error's first label.
+fn error_span(error: &OxcDiagnostic) -> Span {
+  error
+    .labels
+    .as_ref()
+    .and_then(|labels| labels.first())
+    .map(|label| Span::new(label.offset() as u32, (label.offset() + label.len()) as u32))
+    .unwrap_or_default()
+}
+
+/// ESM `import`/`export` in a plain `.js` file whose nearest package.json
+/// doesn't declare `"type": "module"`, so Node would load it as CommonJS.
+/// The package.json is only consulted when `read_package_json` is set.
+fn module_syntax_diagnostic(
+  path: &str,
+  program: &Program,
+  read_package_json: bool,
+) -> Option<Diagnostic> {
+  if Path::new(path).extension() != Some(OsStr::new("js")) {
+    return None;
+  }
+  let declaration = program.body.iter().find(|statement| {
+    matches!(
+      statement,
+      Statement::ImportDeclaration(_)
+        | Statement::ExportAllDeclaration(_)
+        | Statement::ExportDefaultDeclaration(_)
+        | Statement::ExportNamedDeclaration(_)
+    )
+  })?;
+  if read_package_json && in_module_package(path) {
+    return None;
+  }
+  Some(Diagnostic::new(
+    "module-syntax-in-js",
+    "uses ES module syntax in a .js file; rename it to .mjs or set \"type\": \"module\" in package.json",
+    declaration.span(),
+  ))
+}
+
+/// Whether the nearest package.json above the file at `path` sets `"type":
+/// "module"`. URLs have no package.json.
+fn in_module_package(path: &str) -> bool {
+  if path.starts_with("http://") || path.starts_with("https://") {
+    return false;
+  }
+  let Ok(cwd) = std::env::current_dir() else {
+    return false;
+  };
+  let path = cwd.join(path);
+  for dir in path.ancestors().skip(1) {
+    let Ok(manifest) = fs::read_to_string(dir.join("package.json")) else {
+      continue;
+    };
+    return serde_json::from_str::<serde_json::Value>(&manifest)
+      .is_ok_and(|package| package["type"] == "module");
+  }
+  false
+}
+
+/// The part of a function before its parameters, e.g. `async function f`.
+/// Methods start at their parameters, so they keep the whole span.
+fn head_span(function: Span, params: Span) -> Span {
+  if params.start > function.start {
+    Span::new(function.start, params.start)
+  } else {
+    function
+  }
+}
+
+/// The part of a class before its body, e.g. `class A extends B`.
+fn class_head_span(class: &Class) -> Span {
+  let end = class
+    .super_class
+    .as_ref()
+    .map(|super_class| super_class.span().end)
+    .or_else(|| class.id.as_ref().map(|id| id.span.end))
+    .unwrap_or(class.span.start + "class".len() as u32);
+  Span::new(class.span.start, end.min(class.body.span.start))
+}
+
+/// Whether the nearest function around `node` is async, or `None` when the
+/// node sits at the top level of the program.
+fn enclosing_function_async(semantic: &Semantic, node: &AstNode) -> Option<bool> {
+  let nodes = semantic.nodes();
+  let mut current = nodes.parent_node(node.id());
+  while let Some(parent) = current {
+    match parent.kind() {
+      AstKind::Function(func) => return Some(func.r#async),
+      AstKind::ArrowFunctionExpression(arrow) => return Some(arrow.r#async),
+      _ => {}
+    }
+    current = nodes.parent_node(parent.id());
+  }
+  None
+}
+
+/// Iteration features implied by a `[Symbol.asyncIterator]` or
+/// `[Symbol.iterator]` member key, independent of any `for of` loop.
+fn iterator_method_feature(semantic: &Semantic, key: &PropertyKey) -> Option<JsFeature> {
+  let PropertyKey::StaticMemberExpression(member) = key else {
+    return None;
+  };
+  let Expression::Identifier(object) = &member.object else {
+    return None;
+  };
+  if object.name != "Symbol" || !is_global(semantic, object) {
+    return None;
+  }
+  match member.property.name.as_str() {
+    "asyncIterator" => Some(JsFeature::AsyncIteration),
+    "iterator" => Some(JsFeature::SymbolIterator),
+    _ => None,
+  }
+}
+
+/// Expressions that can never evaluate to a thenable, like `42` or `"x"`.
+fn is_non_thenable(expr: &Expression) -> bool {
+  matches!(
+    expr,
+    Expression::NumericLiteral(_)
+      | Expression::StringLiteral(_)
+      | Expression::BooleanLiteral(_)
+      | Expression::NullLiteral(_)
+      | Expression::BigIntLiteral(_)
+      | Expression::RegExpLiteral(_)
+      | Expression::TemplateLiteral(_)
+      | Expression::ArrayExpression(_)
+  )
+}
+
+/// `typeof x === "bigint"` in either operand order.
+fn is_typeof_bigint_check(expr: &BinaryExpression) -> bool {
+  if !expr.operator.is_equality() {
+    return false;
+  }
+  let is_typeof = |side: &Expression| matches!(side, Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::Typeof);
+  let is_bigint = |side: &Expression| matches!(side, Expression::StringLiteral(literal) if literal.value == "bigint");
+  (is_typeof(&expr.left) && is_bigint(&expr.right))
+    || (is_bigint(&expr.left) && is_typeof(&expr.right))
+}
+
+/// Literal-like right-hand sides of `a || b` that read as a default value.
+fn is_default_value(expr: &Expression) -> bool {
+  matches!(
+    expr,
+    Expression::NumericLiteral(_)
+      | Expression::StringLiteral(_)
+      | Expression::BooleanLiteral(_)
+      | Expression::TemplateLiteral(_)
+      | Expression::ArrayExpression(_)
+      | Expression::ObjectExpression(_)
+  )
+}
+
+/// Whether a destructuring pattern is the outermost one, as opposed to a
+/// pattern nested inside another (`{b}` in `{a: {b}}`).
+fn is_outer_pattern(semantic: &Semantic, node: &AstNode) -> bool {
+  let nodes = semantic.nodes();
+  let mut current = nodes.parent_node(node.id());
+  // A default value wraps the pattern without nesting it
+  while let Some(parent) = current {
+    if !matches!(parent.kind(), AstKind::AssignmentPattern(_)) {
+      break;
+    }
+    current = nodes.parent_node(parent.id());
+  }
+  !matches!(
+    current.map(|parent| parent.kind()),
+    Some(
+      AstKind::BindingProperty(_)
+        | AstKind::ArrayPattern(_)
+        | AstKind::ObjectPattern(_)
+        | AstKind::BindingRestElement(_)
+    )
+  )
+}
+
+/// Which later-supported forms a destructuring pattern uses: default values
+/// (`{a = 1}`) and nested patterns (`{a: {b}}`).
+#[derive(Debug, Default)]
+struct PatternShape {
+  defaults: bool,
+  nested: bool,
+}
+
+impl PatternShape {
+  fn visit(&mut self, pattern: &BindingPattern, depth: usize) {
+    match &pattern.kind {
+      BindingPatternKind::BindingIdentifier(_) => {}
+      BindingPatternKind::AssignmentPattern(assignment) => {
+        self.defaults = true;
+        self.visit(&assignment.left, depth);
+      }
+      BindingPatternKind::ObjectPattern(object) => self.visit_object(object, depth),
+      BindingPatternKind::ArrayPattern(array) => self.visit_array(array, depth),
+    }
+  }
+
+  fn visit_object(&mut self, object: &ObjectPattern, depth: usize) {
+    self.nested |= depth > 0;
+    for property in &object.properties {
+      self.visit(&property.value, depth + 1);
+    }
+    if let Some(rest) = &object.rest {
+      self.visit(&rest.argument, depth + 1);
+    }
+  }
+
+  fn visit_array(&mut self, array: &ArrayPattern, depth: usize) {
+    self.nested |= depth > 0;
+    for element in array.elements.iter().flatten() {
+      self.visit(element, depth + 1);
+    }
+    if let Some(rest) = &array.rest {
+      self.visit(&rest.argument, depth + 1);
+    }
+  }
+
+  fn detail(&self) -> Option<String> {
+    match (self.defaults, self.nested) {
+      (true, true) => Some("defaults, nested".to_string()),
+      (true, false) => Some("defaults".to_string()),
+      (false, true) => Some("nested".to_string()),
+      (false, false) => None,
+    }
+  }
+}
+
+/// Collects method names along a call chain in call order:
+/// `a.flatMap(f).at(-1)` yields `["flatMap", "at"]`.
+fn method_chain<'a>(expr: &'a CallExpression) -> Vec<&'a str> {
+  let mut methods = Vec::new();
+  let mut current = expr;
+  while let Expression::StaticMemberExpression(member) = &current.callee {
+    methods.push(member.property.name.as_str());
+    match &member.object {
+      Expression::CallExpression(inner) => current = &**inner,
+      _ => break,
+    }
+  }
+  methods.reverse();
+  methods
+}
+
+/// Whether `ident` resolves to a global rather than a local binding that
+/// shadows it (e.g. `const Object = ...`).
+fn is_global(semantic: &Semantic, ident: &IdentifierReference) -> bool {
+  semantic
+    .symbols()
+    .get_reference(ident.reference_id())
+    .symbol_id()
+    .is_none()
+}
+
+/// Matches `Global.method(...)` calls where `Global` is an unshadowed global
+/// identifier, returning the object and property names.
+fn global_static_call<'a>(
+  semantic: &Semantic,
+  expr: &'a CallExpression,
+) -> Option<(&'a str, &'a str)> {
+  let Expression::StaticMemberExpression(member) = &expr.callee else {
+    return None;
+  };
+  let Expression::Identifier(object) = &member.object else {
+    return None;
+  };
+  if !is_global(semantic, object) {
+    return None;
+  }
+  Some((object.name.as_str(), member.property.name.as_str()))
+}
+
+/// Matches `new Global(...)` where `Global` is an unshadowed global
+/// identifier, returning the constructor name.
+fn global_constructor<'a>(semantic: &Semantic, expr: &'a NewExpression) -> Option<&'a str> {
+  let Expression::Identifier(callee) = &expr.callee else {
+    return None;
+  };
+  if !is_global(semantic, callee) {
+    return None;
+  }
+  Some(callee.name.as_str())
+}
+
+/// Resolves which `Intl` constructor produced `expr`, either directly
+/// (`new Intl.DateTimeFormat().formatRange(...)`) or through a local binding
+/// initialized with one (`const dtf = new Intl.DateTimeFormat()`).
+fn intl_constructor<'a>(semantic: &Semantic<'a>, expr: &'a Expression<'a>) -> Option<&'a str> {
+  let new_expr = match expr {
+    Expression::NewExpression(new_expr) => new_expr,
+    Expression::Identifier(ident) => {
+      let symbol_id = semantic
+        .symbols()
+        .get_reference(ident.reference_id())
+        .symbol_id()?;
+      let AstKind::VariableDeclarator(decl) = semantic.symbol_declaration(symbol_id).kind() else {
+        return None;
+      };
+      let Some(Expression::NewExpression(new_expr)) = &decl.init else {
+        return None;
+      };
+      new_expr
+    }
+    _ => return None,
+  };
+  let Expression::StaticMemberExpression(member) = &new_expr.callee else {
+    return None;
+  };
+  match &member.object {
+    Expression::Identifier(object) if object.name == "Intl" && is_global(semantic, object) => {
+      Some(member.property.name.as_str())
+    }
+    _ => None,
+  }
+}
+
+/// Finds the options of `new Intl.NumberFormat(locales, { ... })` that only
+/// exist since the ES2020 unified NumberFormat, e.g. `style: "unit"` or
+/// `notation`, each with the BCD subfeature for that option. These shipped
+/// years after the constructor itself.
+fn unified_number_format_options(
+  semantic: &Semantic,
+  expr: &NewExpression,
+) -> Vec<(JsFeature, String)> {
+  let Expression::StaticMemberExpression(member) = &expr.callee else {
+    return Vec::new();
+  };
+  let Expression::Identifier(object) = &member.object else {
+    return Vec::new();
+  };
+  if object.name != "Intl" || member.property.name != "NumberFormat" || !is_global(semantic, object)
+  {
+    return Vec::new();
+  }
+  let Some(Expression::ObjectExpression(options)) =
+    expr.arguments.get(1).and_then(|arg| arg.as_expression())
+  else {
+    return Vec::new();
+  };
+  options
+    .properties
+    .iter()
+    .filter_map(|property| {
+      let ObjectPropertyKind::ObjectProperty(property) = property else {
+        return None;
+      };
+      let name = property.key.static_name()?;
+      let feature = match (name.as_ref(), &property.value) {
+        // The unit style is unusable without the `unit` option
+        ("style", Expression::StringLiteral(value)) if value.value == "unit" => {
+          return Some((JsFeature::NumberFormatUnit, "style: \"unit\"".to_string()));
+        }
+        ("compactDisplay", _) => JsFeature::NumberFormatCompactDisplay,
+        ("currencySign", _) => JsFeature::NumberFormatCurrencySign,
+        ("notation", _) => JsFeature::NumberFormatNotation,
+        ("signDisplay", _) => JsFeature::NumberFormatSignDisplay,
+        ("unit", _) => JsFeature::NumberFormatUnit,
+        ("unitDisplay", _) => JsFeature::NumberFormatUnitDisplay,
+        _ => return None,
+      };
+      Some((feature, name.to_string()))
+    })
+    .collect()
+}
+
+/// Method-level `Intl` features, only when the receiver is known to come
+/// from an `Intl` constructor; `formatRange` and `formatToParts` are common
+/// names on user objects too.
+fn intl_method_feature(constructor: &str, method: &str) -> Option<JsFeature> {
+  match (constructor, method) {
+    ("DateTimeFormat", "formatRange") => Some(JsFeature::DateTimeFormatRange),
+    ("DateTimeFormat", "formatToParts") => Some(JsFeature::DateTimeFormatToParts),
+    ("NumberFormat", "formatRange") => Some(JsFeature::NumberFormatRange),
+    ("NumberFormat", "formatToParts") => Some(JsFeature::NumberFormatToParts),
+    _ => None,
+  }
+}
+
+/// Features detected from constructing builtins, e.g. `new Promise(...)`.
+fn constructor_feature(name: &str) -> Option<JsFeature> {
+  match name {
+    "Promise" => Some(JsFeature::Promise),
+    name => typed_array_feature(name),
+  }
+}
+
+/// Concrete typed array constructors.
+fn typed_array_feature(name: &str) -> Option<JsFeature> {
+  match name {
+    "Int8Array" => Some(JsFeature::Int8Array),
+    "Uint8Array" => Some(JsFeature::Uint8Array),
+    "Int16Array" => Some(JsFeature::Int16Array),
+    "Uint16Array" => Some(JsFeature::Uint16Array),
+    "Int32Array" => Some(JsFeature::Int32Array),
+    "Uint32Array" => Some(JsFeature::Uint32Array),
+    "Float32Array" => Some(JsFeature::Float32Array),
+    "Float64Array" => Some(JsFeature::Float64Array),
+    _ => None,
+  }
+}
+
+/// Whether `ident` is the callee of a `new` expression, which reports the
+/// constructor itself.
+fn is_new_callee(semantic: &Semantic, node: &AstNode, ident: &IdentifierReference) -> bool {
+  matches!(
+    semantic.nodes().parent_kind(node.id()),
+    Some(AstKind::NewExpression(expr))
+      if matches!(&expr.callee, Expression::Identifier(callee) if callee.span == ident.span)
+  )
+}
+
+/// Matches `name(...)` calls where `name` is an unshadowed global function.
+fn global_call<'a>(semantic: &Semantic, expr: &'a CallExpression) -> Option<&'a str> {
+  let Expression::Identifier(callee) = &expr.callee else {
+    return None;
+  };
+  if !is_global(semantic, callee) {
+    return None;
+  }
+  Some(callee.name.as_str())
+}
+
+/// Features detected from calling global functions, e.g. `escape(...)`.
+fn global_call_feature(name: &str) -> Option<JsFeature> {
+  match name {
+    "escape" => Some(JsFeature::Escape),
+    "unescape" => Some(JsFeature::Unescape),
+    "structuredClone" => Some(JsFeature::StructuredClone),
+    "queueMicrotask" => Some(JsFeature::QueueMicrotask),
+    "reportError" => Some(JsFeature::ReportError),
+    "require" => Some(JsFeature::CommonJs),
+    "BigInt" => Some(JsFeature::BigInt),
+    _ => None,
+  }
+}
+
+/// `Array.prototype` methods matched by name alone, since the receiver's type
+/// is unknown.
+fn array_method_feature(method: &str) -> Option<JsFeature> {
+  match method {
+    "flat" => Some(JsFeature::ArrayFlat),
+    "flatMap" => Some(JsFeature::ArrayFlatMap),
+    // `at` is a common name on user objects too (`moment().at(...)`, routers),
+    // so this arm is the likeliest source of false positives
+    "at" => Some(JsFeature::RelativeIndexing),
+    _ => None,
+  }
+}
+
+/// Features detected from property access on globals, e.g. `document.all`.
+fn static_member_feature(object: &str, property: &str) -> Option<JsFeature> {
+  match (object, property) {
+    ("document", "all") => Some(JsFeature::DocumentAll),
+    ("module", "exports") | ("exports", _) => Some(JsFeature::CommonJs),
+    ("WebAssembly", _) => Some(JsFeature::WebAssembly),
+    _ => None,
+  }
+}
+
+/// Features detected from static calls on builtins, e.g. `Object.assign(...)`.
+fn static_call_feature(object: &str, property: &str) -> Option<JsFeature> {
+  match (object, property) {
+    ("Object", "assign") => Some(JsFeature::ObjectAssign),
+    ("Object", "groupBy") => Some(JsFeature::ObjectGroupBy),
+    ("Map", "groupBy") => Some(JsFeature::MapGroupBy),
+    ("Array", "isArray") => Some(JsFeature::ArrayIsArray),
+    ("Object", "keys") => Some(JsFeature::ObjectKeys),
+    ("Object", "defineProperty") => Some(JsFeature::ObjectDefineProperty),
+    ("Object", "create") => Some(JsFeature::ObjectCreate),
+    ("Object", "entries") => Some(JsFeature::ObjectEntries),
+    ("Object", "values") => Some(JsFeature::ObjectValues),
+    ("Object", "fromEntries") => Some(JsFeature::ObjectFromEntries),
+    _ => None,
+  }
+}
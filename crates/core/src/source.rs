@@ -0,0 +1,285 @@
+use console::style;
+use futures_util::future::BoxFuture;
+use glob::{glob, Pattern};
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+};
+use url::Url;
+
+use crate::{cache, download::download_with_progress};
+
+/// Where `jsco` gets its sources from. The default [`FsProvider`] reads the
+/// filesystem and the network; embedders and tests can supply their own.
+pub trait SourceProvider: Send + Sync {
+  /// Expands one input (a file, directory, glob or URL) into the sources it
+  /// names.
+  fn resolve(&self, input: &str) -> Vec<String>;
+
+  /// Reads a resolved source, or says why it can't be read.
+  fn read<'a>(&'a self, source: &'a str) -> BoxFuture<'a, Result<String, String>>;
+}
+
+/// Cache entry name for a URL. The fragment never reaches the server, so it's
+/// left out; the query picks a different resource and is kept.
+fn get_cache_key(url: &str) -> String {
+  if let Ok(parsed_url) = Url::parse(url) {
+    let mut base_url = format!(
+      "{}://{}{}",
+      parsed_url.scheme(),
+      parsed_url.host_str().unwrap_or(""),
+      parsed_url.path()
+    );
+    if let Some(query) = parsed_url.query() {
+      base_url.push('?');
+      base_url.push_str(query);
+    }
+    format!("{:x}", md5::compute(base_url))
+  } else {
+    format!("{:x}", md5::compute(url))
+  }
+}
+
+fn is_url(s: &str) -> bool {
+  s.starts_with("http://") || s.starts_with("https://")
+}
+
+/// Rewrites GitHub blob and gist page URLs to their raw content URLs, since
+/// the pages themselves are HTML and won't parse as JavaScript.
+fn raw_github_url(url: &str) -> Option<String> {
+  let parsed_url = Url::parse(url).ok()?;
+  let segments = parsed_url.path_segments()?.collect::<Vec<&str>>();
+  match (parsed_url.host_str()?, segments.as_slice()) {
+    // github.com/<user>/<repo>/blob/<ref>/<path>
+    ("github.com", [user, repo, "blob", rest @ ..]) if rest.len() >= 2 => Some(format!(
+      "https://raw.githubusercontent.com/{}/{}/{}",
+      user,
+      repo,
+      rest.join("/")
+    )),
+    // gist.github.com/<user>/<id>
+    ("gist.github.com", [user, id]) => Some(format!(
+      "https://gist.githubusercontent.com/{}/{}/raw",
+      user, id
+    )),
+    _ => None,
+  }
+}
+
+#[derive(Debug)]
+enum InputType {
+  File(PathBuf),
+  Url(String),
+  Directory(PathBuf),
+  Glob(String),
+}
+
+impl InputType {
+  fn from_str(s: &str) -> Self {
+    if is_url(s) {
+      Self::Url(raw_github_url(s).unwrap_or_else(|| s.to_string()))
+    } else if s.contains('*') {
+      Self::Glob(s.to_string())
+    } else if PathBuf::from(s).is_dir() {
+      Self::Directory(PathBuf::from(s))
+    } else {
+      Self::File(PathBuf::from(s))
+    }
+  }
+}
+
+/// Extensions picked up from directories and globs when none are configured.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "jsx"];
+
+/// Collects the files under `dir` that `is_source` accepts (by default those
+/// with one of `DEFAULT_EXTENSIONS`), walking subdirectories but skipping
+/// `node_modules`. Symlinked directories aren't followed.
+fn scan_dir(dir: &Path, is_source: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+  let mut files = Vec::new();
+  let mut pending = vec![dir.to_path_buf()];
+  while let Some(dir) = pending.pop() {
+    let Ok(entries) = fs::read_dir(&dir) else {
+      continue;
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+      if is_dir {
+        if entry.file_name() != "node_modules" {
+          pending.push(path);
+        }
+      } else if is_source(&path) {
+        files.push(path);
+      }
+    }
+  }
+  files.sort();
+  files
+}
+
+/// Reads local files, directories and globs from disk and downloads URLs.
+#[derive(Debug, Default, Clone)]
+pub struct FsProvider {
+  /// Don't announce directory and glob scans.
+  pub quiet: bool,
+  /// Extensions to pick up from directories and globs, without the dot.
+  /// Empty means [`DEFAULT_EXTENSIONS`].
+  pub extensions: Vec<String>,
+  /// Download URLs again instead of reading them from the cache.
+  pub refresh: bool,
+}
+
+impl FsProvider {
+  fn is_source(&self, path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+      return false;
+    };
+    if self.extensions.is_empty() {
+      DEFAULT_EXTENSIONS.contains(&extension)
+    } else {
+      self.extensions.iter().any(|ext| ext == extension)
+    }
+  }
+}
+
+impl SourceProvider for FsProvider {
+  fn resolve(&self, input: &str) -> Vec<String> {
+    match InputType::from_str(input) {
+      InputType::File(path) => vec![path.to_string_lossy().to_string()],
+      InputType::Url(url) => vec![url],
+      InputType::Directory(dir) => {
+        if !self.quiet {
+          eprintln!(
+            "\n{} Scanning directory: {}",
+            style("📁").bold(),
+            style(&dir.display()).cyan()
+          );
+        }
+        scan_dir(&dir, |path| self.is_source(path))
+          .into_iter()
+          .map(|path| path.to_string_lossy().to_string())
+          .collect()
+      }
+      InputType::Glob(pattern) => {
+        if !self.quiet {
+          eprintln!(
+            "\n{} Scanning files matching: {}",
+            style("🔍").bold(),
+            style(&pattern).cyan()
+          );
+        }
+        let Ok(paths) = glob(&pattern) else {
+          return Vec::new();
+        };
+        paths
+          .flatten()
+          .filter(|path| self.is_source(path))
+          .map(|path| path.to_string_lossy().to_string())
+          .collect()
+      }
+    }
+  }
+
+  fn read<'a>(&'a self, source: &'a str) -> BoxFuture<'a, Result<String, String>> {
+    Box::pin(async move {
+      if !is_url(source) {
+        return fs::read_to_string(source).map_err(|err| err.to_string());
+      }
+
+      let cache_key = get_cache_key(source);
+      if self.refresh {
+        let _ = fs::remove_file(cache::path(&cache_key));
+      }
+      download_with_progress(source.to_string(), cache_key)
+        .await
+        .map_err(|err| err.to_string())
+    })
+  }
+}
+
+/// Serves sources from memory. Inputs match file names exactly, or as glob
+/// patterns over all file names.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryProvider {
+  files: BTreeMap<String, String>,
+}
+
+impl MemoryProvider {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_file(mut self, path: impl Into<String>, source_code: impl Into<String>) -> Self {
+    self.files.insert(path.into(), source_code.into());
+    self
+  }
+}
+
+impl SourceProvider for MemoryProvider {
+  fn resolve(&self, input: &str) -> Vec<String> {
+    if self.files.contains_key(input) {
+      return vec![input.to_string()];
+    }
+    match Pattern::new(input) {
+      Ok(pattern) => self
+        .files
+        .keys()
+        .filter(|path| pattern.matches(path))
+        .cloned()
+        .collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  fn read<'a>(&'a self, source: &'a str) -> BoxFuture<'a, Result<String, String>> {
+    Box::pin(async move {
+      self
+        .files
+        .get(source)
+        .cloned()
+        .ok_or_else(|| "no such file".to_string())
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cache_keys_tell_queries_apart() {
+    assert_ne!(
+      get_cache_key("https://x/a.js?v=1"),
+      get_cache_key("https://x/a.js?v=2")
+    );
+    assert_eq!(
+      get_cache_key("https://x/a.js?v=1"),
+      get_cache_key("https://x/a.js?v=1#main")
+    );
+  }
+
+  #[test]
+  fn github_blob_urls_point_at_raw_content() {
+    assert_eq!(
+      raw_github_url("https://github.com/acme/app/blob/main/src/index.js").as_deref(),
+      Some("https://raw.githubusercontent.com/acme/app/main/src/index.js")
+    );
+  }
+
+  #[test]
+  fn gist_urls_point_at_raw_content() {
+    assert_eq!(
+      raw_github_url("https://gist.github.com/octocat/6cad326836d38bd3a7ae").as_deref(),
+      Some("https://gist.githubusercontent.com/octocat/6cad326836d38bd3a7ae/raw")
+    );
+  }
+
+  #[test]
+  fn other_urls_are_left_alone() {
+    assert_eq!(
+      raw_github_url("https://example.com/acme/app/blob/main/a.js"),
+      None
+    );
+  }
+}
@@ -0,0 +1,58 @@
+use std::cmp::Ordering;
+
+use crate::feature::BrowserSupport;
+
+/// Numeric components of a version such as `15.4`, `≤79` or the `15.2-15.3`
+/// ranges browserslist uses for iOS Safari (the lower bound is taken).
+pub fn parse_version(version: &str) -> Vec<u32> {
+  version
+    .trim_start_matches(|c: char| !c.is_ascii_digit())
+    .split('-')
+    .next()
+    .unwrap_or_default()
+    .split('.')
+    .map(|part| part.parse().unwrap_or(0))
+    .collect()
+}
+
+/// Compares versions component by component, so `15.4` > `15` == `15.0`.
+pub fn compare_versions(a: &[u32], b: &[u32]) -> Ordering {
+  let len = a.len().max(b.len());
+  (0..len)
+    .map(|i| {
+      let left = a.get(i).copied().unwrap_or(0);
+      let right = b.get(i).copied().unwrap_or(0);
+      left.cmp(&right)
+    })
+    .find(|ordering| ordering.is_ne())
+    .unwrap_or(Ordering::Equal)
+}
+
+/// Orders BCD `version_added` values by how much they demand: `true`
+/// (every version) first, then numeric versions, `preview`, and `false`
+/// (no version) last.
+pub fn compare_required(a: &str, b: &str) -> Ordering {
+  let rank = |version: &str| match version {
+    "true" => 0,
+    "preview" => 2,
+    "false" => 3,
+    _ => 1,
+  };
+  rank(a)
+    .cmp(&rank(b))
+    .then_with(|| compare_versions(&parse_version(a), &parse_version(b)))
+}
+
+/// Raises each browser's entry in `required` to `support`'s version where
+/// that one is more demanding.
+pub fn raise_required(required: &mut BrowserSupport, support: &BrowserSupport) {
+  for (browser, version) in support {
+    let satisfied = matches!(
+      required.get(browser),
+      Some(current) if compare_required(version, current) != Ordering::Greater
+    );
+    if !satisfied {
+      required.insert(browser.clone(), version.clone());
+    }
+  }
+}
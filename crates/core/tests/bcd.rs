@@ -0,0 +1,74 @@
+mod common;
+
+#[tokio::test]
+async fn feature_support_resolves_a_known_key() {
+  common::setup().await;
+  let support = jsco::feature_support("javascript.operators.optional_chaining")
+    .await
+    .unwrap()
+    .expect("optional chaining is in BCD");
+  assert_eq!(support.get("chrome").map(String::as_str), Some("80"));
+  assert_eq!(support.get("safari").map(String::as_str), Some("13.1"));
+}
+
+#[tokio::test]
+async fn feature_support_returns_none_for_an_unknown_key() {
+  common::setup().await;
+  let support = jsco::feature_support("javascript.operators.no_such_operator")
+    .await
+    .unwrap();
+  assert_eq!(support, None);
+}
+
+#[tokio::test]
+async fn features_without_an_mdn_url_link_to_a_search() {
+  let options = {
+    let mut options = jsco::Options::default();
+    options
+      .enabled
+      .insert(jsco::feature::JsFeature::ArraySortStability);
+    options
+  };
+  let report = common::check("input.js", "a?.b;\nlist.sort(cmp);\n", &options).await;
+  let url_of = |key: &str| {
+    report
+      .found_features
+      .iter()
+      .find(|feature| feature.feat_type.key() == key)
+      .map(|feature| feature.mdn_url.clone())
+      .unwrap()
+  };
+
+  assert_eq!(
+    url_of("javascript.builtins.Array.sort.stable_sorting"),
+    "https://developer.mozilla.org/en-US/search?q=Array.sort.stable_sorting"
+  );
+  let documented = url_of("javascript.operators.optional_chaining");
+  assert!(!documented.contains("/search?"), "{}", documented);
+}
+
+#[tokio::test]
+async fn each_feature_links_to_its_own_mdn_page() {
+  let report = common::check("input.js", "a?.b;\nc ?? d;\n", &jsco::Options::default()).await;
+  let url_of = |feature: jsco::feature::JsFeature| {
+    report
+      .found_features
+      .iter()
+      .find(|found| found.feat_type == feature)
+      .map(|found| found.mdn_url.clone())
+      .unwrap()
+  };
+
+  let nullish = url_of(jsco::feature::JsFeature::NullishCoalescing);
+  assert!(
+    nullish.ends_with("/Operators/Nullish_coalescing"),
+    "{}",
+    nullish
+  );
+  let optional = url_of(jsco::feature::JsFeature::OptionalChaining);
+  assert!(
+    optional.ends_with("/Operators/Optional_chaining"),
+    "{}",
+    optional
+  );
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+/// Serves BCD lookups from the trimmed snapshot in `fixtures/bcd.json` and
+/// caches into a scratch directory, so tests never touch the network.
+pub async fn setup() {
+  jsco::cache::set_dir(std::env::temp_dir().join(format!("jsco-test-{}", std::process::id())));
+  jsco::bcd::use_bcd_file(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bcd.json"),
+  );
+  jsco::download::set_offline(true);
+  jsco::bcd::load()
+    .await
+    .expect("fixture BCD data should load");
+}
+
+/// Analyzes `code` as if read from `path`, with `options`.
+#[allow(dead_code)]
+pub async fn check(path: &str, code: &str, options: &jsco::Options) -> jsco::report::Report {
+  setup().await;
+  let mut report = jsco::report::Report::new(path.to_string(), code.to_string());
+  report.check_feature_with(options);
+  report.prepare_output();
+  report
+}
+
+/// Locations found for the feature with BCD key `key`, in source order.
+#[allow(dead_code)]
+pub fn locations(report: &jsco::report::Report, key: &str) -> Vec<jsco::feature::Location> {
+  let mut locations = report
+    .found_features
+    .iter()
+    .filter(|feature| feature.feat_type.key() == key)
+    .flat_map(|feature| feature.locations.clone())
+    .collect::<Vec<_>>();
+  locations.sort_by_key(|location| location.start);
+  locations
+}
@@ -0,0 +1,171 @@
+mod common;
+
+#[tokio::test]
+async fn await_in_a_plain_function_is_reported() {
+  common::setup().await;
+  let source = "function load(url) {\n  return await fetch(url);\n}\n";
+  let report = jsco::analyze_source("input.mjs", source).await.unwrap();
+
+  assert!(report.has_parse_errors());
+  let diagnostics = report.diagnostics.lock().unwrap();
+  let diagnostic = diagnostics
+    .iter()
+    .find(|diagnostic| diagnostic.code == "await-outside-async")
+    .expect("await-outside-async diagnostic");
+  assert_eq!(diagnostic.location.start_line, 2);
+  assert!(diagnostic.location.code.starts_with("await"));
+}
+
+/// A scratch package directory with the given package.json.
+fn package_dir(name: &str, manifest: &str) -> std::path::PathBuf {
+  let dir = std::env::temp_dir().join(format!("jsco-test-{}-{}", name, std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  std::fs::write(dir.join("package.json"), manifest).unwrap();
+  dir
+}
+
+fn reading_package_json() -> jsco::Options {
+  jsco::Options {
+    read_package_json: true,
+    ..jsco::Options::default()
+  }
+}
+
+fn has_module_syntax_diagnostic(report: &jsco::report::Report) -> bool {
+  report
+    .diagnostics
+    .lock()
+    .unwrap()
+    .iter()
+    .any(|diagnostic| diagnostic.code == "module-syntax-in-js")
+}
+
+#[tokio::test]
+async fn esm_in_a_commonjs_package_is_reported() {
+  let dir = package_dir("commonjs", r#"{ "name": "app" }"#);
+  let path = dir.join("index.js");
+  let report = common::check(
+    path.to_str().unwrap(),
+    "import { a } from './a.js';\n",
+    &reading_package_json(),
+  )
+  .await;
+  assert!(has_module_syntax_diagnostic(&report));
+}
+
+#[tokio::test]
+async fn esm_in_a_module_package_is_fine() {
+  let dir = package_dir("module", r#"{ "name": "app", "type": "module" }"#);
+  let path = dir.join("src").join("index.js");
+  let report = common::check(
+    path.to_str().unwrap(),
+    "export const a = 1;\n",
+    &reading_package_json(),
+  )
+  .await;
+  assert!(!has_module_syntax_diagnostic(&report));
+}
+
+#[tokio::test]
+async fn in_memory_sources_never_consult_package_json() {
+  let dir = package_dir("in-memory", r#"{ "name": "app", "type": "module" }"#);
+  let path = dir.join("index.js");
+  common::setup().await;
+  let report = jsco::analyze_source(path.to_str().unwrap(), "export const a = 1;\n")
+    .await
+    .unwrap();
+  // Without the lookup a .js file is taken to be CommonJS
+  assert!(has_module_syntax_diagnostic(&report));
+}
+
+#[tokio::test]
+async fn chain_analysis_reports_the_highest_es_year() {
+  let options = jsco::Options {
+    chain_analysis: true,
+    ..jsco::Options::default()
+  };
+  let report = common::check(
+    "input.js",
+    "const last = list.flatMap(split).at(-1);\n",
+    &options,
+  )
+  .await;
+
+  let diagnostics = report.diagnostics.lock().unwrap();
+  let chains = diagnostics
+    .iter()
+    .filter(|diagnostic| diagnostic.code == "method-chain")
+    .collect::<Vec<_>>();
+  assert_eq!(chains.len(), 1);
+  assert_eq!(chains[0].location.detail.as_deref(), Some("ES2022"));
+  assert!(
+    chains[0].message.contains("flatMap().at()"),
+    "{}",
+    chains[0].message
+  );
+}
+
+fn diagnostic_codes(report: &jsco::report::Report) -> Vec<String> {
+  report
+    .diagnostics
+    .lock()
+    .unwrap()
+    .iter()
+    .map(|diagnostic| diagnostic.code.clone())
+    .collect()
+}
+
+#[tokio::test]
+async fn or_defaults_get_a_nullish_advisory_when_asked_for() {
+  let options = jsco::Options {
+    nullish_advisory: true,
+    ..jsco::Options::default()
+  };
+  let report = common::check("input.js", "const count = x || 0;\n", &options).await;
+  assert_eq!(diagnostic_codes(&report), ["prefer-nullish-coalescing"]);
+  // Advisory only, not a compatibility finding
+  assert!(report.found_features.is_empty());
+
+  let report = common::check("input.js", "const both = x && y;\n", &options).await;
+  assert!(diagnostic_codes(&report).is_empty());
+  let report = common::check(
+    "input.js",
+    "const count = x || 0;\n",
+    &jsco::Options::default(),
+  )
+  .await;
+  assert!(diagnostic_codes(&report).is_empty());
+}
+
+#[tokio::test]
+async fn awaiting_a_literal_is_flagged() {
+  let source = "async function run() {\n  await 42;\n  await fetch(url);\n}\n";
+  let report = common::check("input.js", source, &jsco::Options::default()).await;
+
+  let diagnostics = report.diagnostics.lock().unwrap();
+  let flagged = diagnostics
+    .iter()
+    .filter(|diagnostic| diagnostic.code == "await-non-thenable")
+    .map(|diagnostic| diagnostic.location.code.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(flagged, ["await 42"]);
+}
+
+#[tokio::test]
+async fn mixing_nullish_with_logical_operators_suggests_parentheses() {
+  common::setup().await;
+  let source = "const name = input ?? fallback || \"anonymous\";\n";
+  let report = jsco::analyze_source("input.js", source).await.unwrap();
+
+  assert!(report.has_parse_errors());
+  let diagnostics = report.diagnostics.lock().unwrap();
+  let diagnostic = diagnostics
+    .iter()
+    .find(|diagnostic| diagnostic.code == "mixed-nullish-logical")
+    .expect("mixed-nullish-logical diagnostic");
+  assert_eq!(
+    diagnostic.message,
+    "`??` can't be mixed with `||` or `&&` without parentheses; write `(a ?? b) || c` or `a ?? (b || c)`"
+  );
+  assert_eq!(diagnostic.location.start_line, 1);
+}
@@ -0,0 +1,533 @@
+mod common;
+
+use jsco::{feature::JsFeature, Options};
+
+fn enable(feature: JsFeature) -> Options {
+  let mut options = Options::default();
+  options.enabled.insert(feature);
+  options
+}
+
+#[tokio::test]
+async fn environment_globals_are_flagged_by_name() {
+  let source = "const width = window.innerWidth;\nconst env = process.env.NODE_ENV;\n";
+  let report = common::check("input.js", source, &enable(JsFeature::EnvironmentGlobal)).await;
+
+  let details = common::locations(&report, JsFeature::EnvironmentGlobal.key())
+    .into_iter()
+    .map(|location| location.detail)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    details,
+    [Some("window".to_string()), Some("process".to_string())]
+  );
+}
+
+#[tokio::test]
+async fn environment_globals_are_off_by_default() {
+  let report = common::check("input.js", "window.alert(1);\n", &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::EnvironmentGlobal.key()).is_empty());
+}
+
+#[tokio::test]
+async fn bigint_literals_carry_browser_support() {
+  let report = common::check(
+    "input.js",
+    "const max = 9007199254740991n;\n",
+    &Options::default(),
+  )
+  .await;
+
+  let bigint = report
+    .found_features
+    .iter()
+    .find(|feature| feature.feat_type == JsFeature::BigInt)
+    .expect("BigInt literal is reported");
+  let support = bigint.support.lock().unwrap();
+  assert!(!support.is_empty());
+  assert_eq!(support.get("safari").map(String::as_str), Some("14"));
+}
+
+#[tokio::test]
+async fn top_level_await_is_told_apart_from_await() {
+  let source = "const config = await load();\nasync function run() {\n  await start(config);\n}\n";
+  let report = common::check("input.mjs", source, &Options::default()).await;
+
+  let top_level = common::locations(&report, JsFeature::TopLevelAwait.key());
+  assert_eq!(top_level.len(), 1);
+  assert_eq!(top_level[0].start_line, 1);
+  let nested = common::locations(&report, JsFeature::Await.key());
+  assert_eq!(nested.len(), 1);
+  assert_eq!(nested[0].start_line, 3);
+
+  let top_level_support = report
+    .found_features
+    .iter()
+    .find(|feature| feature.feat_type == JsFeature::TopLevelAwait)
+    .map(|feature| feature.support.lock().unwrap().clone())
+    .unwrap();
+  assert_eq!(
+    top_level_support.get("chrome").map(String::as_str),
+    Some("89")
+  );
+}
+
+#[tokio::test]
+async fn sort_stability_is_an_informational_opt_in() {
+  let report = common::check(
+    "input.js",
+    "arr.sort(cmp);\n",
+    &enable(JsFeature::ArraySortStability),
+  )
+  .await;
+
+  assert!(JsFeature::ArraySortStability.is_informational());
+  let locations = common::locations(&report, JsFeature::ArraySortStability.key());
+  assert_eq!(locations.len(), 1);
+  assert_eq!(locations[0].code, "arr.sort(cmp)");
+  assert!(locations[0].detail.as_deref().unwrap().contains("ES2019"));
+}
+
+#[tokio::test]
+async fn an_optional_chain_is_counted_once() {
+  let report = common::check("input.js", "a?.b?.c;\n", &Options::default()).await;
+  let locations = common::locations(&report, JsFeature::OptionalChaining.key());
+  assert_eq!(locations.len(), 1);
+  assert_eq!(locations[0].code, "a?.b?.c");
+}
+
+#[tokio::test]
+async fn unary_operators_stay_outside_the_chain_span() {
+  for source in ["typeof a?.b;\n", "delete a?.b;\n"] {
+    let report = common::check("input.js", source, &Options::default()).await;
+    let locations = common::locations(&report, JsFeature::OptionalChaining.key());
+    assert_eq!(locations.len(), 1, "{}", source);
+    assert_eq!(locations[0].code, "a?.b", "{}", source);
+  }
+}
+
+#[tokio::test]
+async fn assignment_spans_cover_both_operands() {
+  let report = common::check("input.js", "x ||= y;\nn **= 2;\n", &Options::default()).await;
+
+  let logical = common::locations(&report, JsFeature::LogicalAssignment.key());
+  assert_eq!(logical.len(), 1);
+  assert_eq!(logical[0].code, "x ||= y");
+  let exponentiation = common::locations(&report, JsFeature::ExponentiationAssignment.key());
+  assert_eq!(exponentiation.len(), 1);
+  assert_eq!(exponentiation[0].code, "n **= 2");
+}
+
+#[tokio::test]
+async fn classes_are_reported_on_their_head() {
+  let source = "class A extends Base {\n  run() {}\n}\nconst B = class {\n  run() {}\n};\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let codes = common::locations(&report, JsFeature::ClassSyntax.key())
+    .into_iter()
+    .map(|location| location.code)
+    .collect::<Vec<_>>();
+  assert_eq!(codes, ["class A extends Base", "class"]);
+}
+
+#[tokio::test]
+async fn custom_detectors_fire_and_resolve_support() {
+  let key = "javascript.builtins.Array.reduceRight";
+  let options = Options {
+    custom_methods: vec![jsco::CustomMethod::new("reduceRight", key)],
+    ..Options::default()
+  };
+  let report = common::check("input.js", "list.reduceRight(add, 0);\n", &options).await;
+
+  let custom = report
+    .found_features
+    .iter()
+    .find(|feature| feature.feat_type.key() == key)
+    .expect("custom detector fires");
+  assert_eq!(custom.locations.len(), 1);
+  assert_eq!(
+    custom
+      .support
+      .lock()
+      .unwrap()
+      .get("safari")
+      .map(String::as_str),
+    Some("5")
+  );
+}
+
+#[test]
+fn custom_keys_are_interned() {
+  let key = "javascript.builtins.Array.copyWithin";
+  let first = jsco::CustomMethod::new("copyWithin", key).feature;
+  let second = jsco::CustomMethod::new("copyWithin", &key.to_string()).feature;
+  assert_eq!(first, second);
+  assert!(std::ptr::eq(first.key(), second.key()));
+}
+
+#[tokio::test]
+async fn object_assign_is_detected_on_the_global_only() {
+  let report = common::check(
+    "input.js",
+    "const merged = Object.assign({}, defaults);\n",
+    &Options::default(),
+  )
+  .await;
+  let locations = common::locations(&report, JsFeature::ObjectAssign.key());
+  assert_eq!(locations.len(), 1);
+  assert_eq!(locations[0].code, "Object.assign({}, defaults)");
+
+  for source in [
+    "helpers.assign({}, defaults);\n",
+    "const Object = { assign() {} };\nObject.assign({}, defaults);\n",
+  ] {
+    let report = common::check("input.js", source, &Options::default()).await;
+    assert!(
+      common::locations(&report, JsFeature::ObjectAssign.key()).is_empty(),
+      "{}",
+      source
+    );
+  }
+}
+
+#[tokio::test]
+async fn promise_construction_and_finally_are_detected() {
+  let source = "const p = new Promise((resolve) => resolve(1));\np.finally(done);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let promise = common::locations(&report, JsFeature::Promise.key());
+  assert_eq!(promise.len(), 1);
+  assert_eq!(promise[0].start_line, 1);
+  let finally = common::locations(&report, JsFeature::PromiseFinally.key());
+  assert_eq!(finally.len(), 1);
+  assert_eq!(finally[0].code, "p.finally(done)");
+}
+
+#[tokio::test]
+async fn grouping_helpers_are_detected_with_their_keys() {
+  let source =
+    "const byType = Object.groupBy(items, kind);\nconst byOwner = Map.groupBy(items, owner);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let object = common::locations(&report, "javascript.builtins.Object.groupBy");
+  assert_eq!(object.len(), 1);
+  assert_eq!(object[0].code, "Object.groupBy(items, kind)");
+  let map = common::locations(&report, "javascript.builtins.Map.groupBy");
+  assert_eq!(map.len(), 1);
+  assert_eq!(map[0].code, "Map.groupBy(items, owner)");
+}
+
+#[tokio::test]
+async fn intl_methods_follow_the_receiver_constructor() {
+  let source = "const dtf = new Intl.DateTimeFormat('en');\ndtf.formatRange(a, b);\nconst nf = new Intl.NumberFormat('en');\nnf.formatToParts(n);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let range = common::locations(&report, JsFeature::DateTimeFormatRange.key());
+  assert_eq!(range.len(), 1);
+  assert_eq!(range[0].code, "dtf.formatRange(a, b)");
+  assert_eq!(
+    common::locations(&report, JsFeature::NumberFormatToParts.key()).len(),
+    1
+  );
+  assert!(common::locations(&report, JsFeature::DateTimeFormatToParts.key()).is_empty());
+}
+
+#[tokio::test]
+async fn module_level_for_await_is_top_level_await() {
+  let source = "for await (const chunk of stream) {\n  use(chunk);\n}\nasync function drain() {\n  for await (const chunk of stream) {}\n}\n";
+  let report = common::check("input.mjs", source, &Options::default()).await;
+
+  let iteration = common::locations(&report, JsFeature::AsyncIteration.key());
+  assert_eq!(iteration.len(), 2);
+  let top_level = common::locations(&report, JsFeature::TopLevelAwait.key());
+  assert_eq!(top_level.len(), 1);
+  assert_eq!(top_level[0].start_line, 1);
+}
+
+#[tokio::test]
+async fn webassembly_is_detected_on_the_global_only() {
+  let source =
+    "WebAssembly.instantiate(bytes);\nconst memory = new WebAssembly.Memory({ initial: 1 });\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  let codes = common::locations(&report, JsFeature::WebAssembly.key())
+    .into_iter()
+    .map(|location| location.code)
+    .collect::<Vec<_>>();
+  assert_eq!(codes, ["WebAssembly.instantiate", "WebAssembly.Memory"]);
+
+  let shadowed = "const WebAssembly = shim();\nWebAssembly.instantiate(bytes);\n";
+  let report = common::check("input.js", shadowed, &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::WebAssembly.key()).is_empty());
+}
+
+#[tokio::test]
+async fn es5_object_helpers_are_opt_in() {
+  let source =
+    "Object.defineProperty(target, 'x', { get() {} });\nconst proto = Object.create(null);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::ObjectDefineProperty.key()).is_empty());
+  assert!(common::locations(&report, JsFeature::ObjectCreate.key()).is_empty());
+
+  let mut options = enable(JsFeature::ObjectDefineProperty);
+  options.enabled.insert(JsFeature::ObjectCreate);
+  let report = common::check("input.js", source, &options).await;
+  assert_eq!(
+    common::locations(&report, JsFeature::ObjectDefineProperty.key()).len(),
+    1
+  );
+  assert_eq!(
+    common::locations(&report, JsFeature::ObjectCreate.key()).len(),
+    1
+  );
+
+  let shadowed = "const Object = shim;\nObject.create(null);\n";
+  let report = common::check("input.js", shadowed, &options).await;
+  assert!(common::locations(&report, JsFeature::ObjectCreate.key()).is_empty());
+}
+
+#[tokio::test]
+async fn destructuring_records_defaults_and_nesting() {
+  let source = "const { a } = obj;\nconst { b = 1 } = obj;\nconst { c: { d } } = obj;\nconst [e = 1, [f]] = list;\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let details = common::locations(&report, JsFeature::Destructuring.key())
+    .into_iter()
+    .map(|location| (location.code, location.detail))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    details,
+    [
+      ("{ a }".to_string(), None),
+      ("{ b = 1 }".to_string(), Some("defaults".to_string())),
+      ("{ c: { d } }".to_string(), Some("nested".to_string())),
+      (
+        "[e = 1, [f]]".to_string(),
+        Some("defaults, nested".to_string())
+      ),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn typed_arrays_are_detected_once_per_use() {
+  let source = "const bytes = new Int8Array(8);\nconst view = Float64Array.from(list);\nconst part = bytes.subarray(2);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let int8 = common::locations(&report, JsFeature::Int8Array.key());
+  assert_eq!(int8.len(), 1);
+  assert_eq!(int8[0].code, "new Int8Array(8)");
+  let float64 = common::locations(&report, JsFeature::Float64Array.key());
+  assert_eq!(float64.len(), 1);
+  assert_eq!(float64[0].code, "Float64Array");
+  let generic = common::locations(&report, JsFeature::TypedArray.key());
+  assert_eq!(generic.len(), 1);
+  assert_eq!(generic[0].code, "bytes.subarray(2)");
+}
+
+#[tokio::test]
+async fn class_static_blocks_are_detected() {
+  let source = "class Config {\n  static {\n    Config.ready = true;\n  }\n}\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let blocks = common::locations(&report, JsFeature::ClassStaticBlock.key());
+  assert_eq!(blocks.len(), 1);
+  assert_eq!(blocks[0].start_line, 2);
+}
+
+#[tokio::test]
+async fn a_decorated_class_reports_one_decorator() {
+  let report = common::check("input.js", "@sealed class Foo {}\n", &Options::default()).await;
+
+  assert!(!report.has_parse_errors());
+  let decorators = common::locations(&report, JsFeature::Decorator.key());
+  assert_eq!(decorators.len(), 1);
+  assert_eq!(decorators[0].code, "@sealed");
+}
+
+#[tokio::test]
+async fn object_and_array_destructuring_are_detected() {
+  let source = "const { a, ...others } = obj;\nconst [x, ...rest] = arr;\n[x, a] = [a, x];\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let codes = common::locations(&report, JsFeature::Destructuring.key())
+    .into_iter()
+    .map(|location| location.code)
+    .collect::<Vec<_>>();
+  assert_eq!(codes, ["{ a, ...others }", "[x, ...rest]", "[x, a]"]);
+  // Only the object rest needs ES2018 rest/spread
+  let rest = common::locations(&report, JsFeature::RestSpread.key());
+  assert_eq!(rest.len(), 1);
+  assert_eq!(rest[0].code, "...others");
+}
+
+#[tokio::test]
+async fn features_without_support_data_inherit_none() {
+  let source = "const name = user?.name;\nconst fs = require('fs');\n";
+  let report = common::check("input.js", source, &enable(JsFeature::CommonJs)).await;
+
+  let support_of = |feature: JsFeature| {
+    report
+      .found_features
+      .iter()
+      .find(|found| found.feat_type == feature)
+      .map(|found| found.support.lock().unwrap().clone())
+      .unwrap()
+  };
+  assert!(support_of(JsFeature::CommonJs).is_empty());
+  assert_eq!(
+    support_of(JsFeature::OptionalChaining)
+      .get("chrome")
+      .map(String::as_str),
+    Some("80")
+  );
+}
+
+#[tokio::test]
+async fn intl_methods_need_an_intl_receiver() {
+  let source = "new Foo().formatToParts(n);\nfunction show(formatter) {\n  return formatter.formatRange(a, b);\n}\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  for feature in [
+    JsFeature::DateTimeFormatRange,
+    JsFeature::DateTimeFormatToParts,
+    JsFeature::NumberFormatRange,
+    JsFeature::NumberFormatToParts,
+  ] {
+    assert!(
+      common::locations(&report, feature.key()).is_empty(),
+      "{:?}",
+      feature
+    );
+  }
+}
+
+#[tokio::test]
+async fn a_shadowed_intl_is_not_the_builtin() {
+  let source = "const Intl = polyfill();\nconst dtf = new Intl.DateTimeFormat('en');\ndtf.formatRange(a, b);\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::DateTimeFormatRange.key()).is_empty());
+}
+
+#[tokio::test]
+async fn unified_number_format_options_use_their_own_subfeatures() {
+  let source =
+    "const size = new Intl.NumberFormat(undefined, { style: \"unit\", unit: \"byte\" });\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let unit = report
+    .found_features
+    .iter()
+    .find(|feature| feature.feat_type == JsFeature::NumberFormatUnit)
+    .expect("unit option is reported");
+  assert_eq!(
+    unit.feat_type.key(),
+    "javascript.builtins.Intl.NumberFormat.NumberFormat.options_unit_parameter"
+  );
+  assert_eq!(unit.locations.len(), 1);
+  assert_eq!(unit.locations[0].detail.as_deref(), Some("style: \"unit\""));
+  assert_eq!(
+    unit
+      .support
+      .lock()
+      .unwrap()
+      .get("safari")
+      .map(String::as_str),
+    Some("14.1")
+  );
+  assert!(common::locations(&report, JsFeature::NumberFormatNotation.key()).is_empty());
+}
+
+#[tokio::test]
+async fn escape_and_document_all_are_flagged_as_deprecated() {
+  let mut options = enable(JsFeature::Escape);
+  options.enabled.insert(JsFeature::DocumentAll);
+  let source = "const query = escape(input);\nif (document.all) {\n  legacy();\n}\n";
+  let report = common::check("input.js", source, &options).await;
+
+  for feature in [JsFeature::Escape, JsFeature::DocumentAll] {
+    let found = report
+      .found_features
+      .iter()
+      .find(|found| found.feat_type == feature)
+      .unwrap_or_else(|| panic!("{:?} is reported", feature));
+    assert_eq!(found.locations.len(), 1, "{:?}", feature);
+    assert!(found.deprecated, "{:?}", feature);
+  }
+}
+
+#[tokio::test]
+async fn queue_microtask_and_report_error_are_detected() {
+  let source = "queueMicrotask(() => flush());\ntry {\n  run();\n} catch (error) {\n  reportError(error);\n}\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let microtask = common::locations(&report, JsFeature::QueueMicrotask.key());
+  assert_eq!(microtask.len(), 1);
+  assert_eq!(microtask[0].code, "queueMicrotask(() => flush())");
+  let report_error = common::locations(&report, JsFeature::ReportError.key());
+  assert_eq!(report_error.len(), 1);
+  assert_eq!(report_error[0].start_line, 5);
+}
+
+#[tokio::test]
+async fn array_is_array_and_object_keys_are_opt_in() {
+  let source = "if (Array.isArray(value)) {\n  count(Object.keys(value[0]));\n}\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::ArrayIsArray.key()).is_empty());
+  assert!(common::locations(&report, JsFeature::ObjectKeys.key()).is_empty());
+
+  let mut options = enable(JsFeature::ArrayIsArray);
+  options.enabled.insert(JsFeature::ObjectKeys);
+  let report = common::check("input.js", source, &options).await;
+  let is_array = common::locations(&report, JsFeature::ArrayIsArray.key());
+  assert_eq!(is_array.len(), 1);
+  assert_eq!(is_array[0].code, "Array.isArray(value)");
+  let keys = common::locations(&report, JsFeature::ObjectKeys.key());
+  assert_eq!(keys.len(), 1);
+  assert_eq!(keys[0].code, "Object.keys(value[0])");
+}
+
+#[tokio::test]
+async fn require_and_module_exports_are_commonjs() {
+  let source = "const path = require('path');\nmodule.exports = { join: path.join };\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+  assert!(common::locations(&report, JsFeature::CommonJs.key()).is_empty());
+
+  let report = common::check("input.js", source, &enable(JsFeature::CommonJs)).await;
+  let codes = common::locations(&report, JsFeature::CommonJs.key())
+    .into_iter()
+    .map(|location| location.code)
+    .collect::<Vec<_>>();
+  assert_eq!(codes, ["require('path')", "module.exports"]);
+}
+
+#[tokio::test]
+async fn symbol_async_iterator_methods_imply_async_iteration() {
+  let source = "class Feed {\n  async *[Symbol.asyncIterator]() {\n    yield* this.items;\n  }\n}\nconst range = {\n  [Symbol.iterator]() {\n    return walk();\n  },\n};\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let iteration = common::locations(&report, JsFeature::AsyncIteration.key());
+  assert_eq!(iteration.len(), 1);
+  assert_eq!(iteration[0].start_line, 2);
+  let iterator = common::locations(&report, JsFeature::SymbolIterator.key());
+  assert_eq!(iterator.len(), 1);
+  assert_eq!(iterator[0].start_line, 7);
+}
+
+#[tokio::test]
+async fn logical_assignment_to_members_is_detected() {
+  let source = "config.retries ??= 3;\ncache[key] ||= compute(key);\nstate.flags[0] &&= ready;\n";
+  let report = common::check("input.js", source, &Options::default()).await;
+
+  let codes = common::locations(&report, JsFeature::LogicalAssignment.key())
+    .into_iter()
+    .map(|location| location.code)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    codes,
+    [
+      "config.retries ??= 3",
+      "cache[key] ||= compute(key)",
+      "state.flags[0] &&= ready"
+    ]
+  );
+}
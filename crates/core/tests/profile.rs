@@ -0,0 +1,53 @@
+mod common;
+
+use std::sync::Arc;
+
+use jsco::{jsco_stream, MemoryProvider, Options};
+
+#[tokio::test]
+async fn the_profile_covers_every_pipeline_stage() {
+  common::setup().await;
+  let provider = MemoryProvider::new()
+    .with_file("a.js", "a?.b;\n")
+    .with_file("b.js", "a ?? b;\n");
+  let mut stream = jsco_stream(
+    vec!["*.js".to_string()],
+    Options::default(),
+    Arc::new(provider),
+  )
+  .await
+  .unwrap();
+  while stream.next().await.is_some() {}
+
+  let profile = stream.finish().await.to_string();
+  for stage in ["input resolution", "download", "parse", "feature check"] {
+    assert!(profile.contains(stage), "{}", profile);
+  }
+}
+
+#[tokio::test]
+async fn identical_sources_are_parsed_once() {
+  common::setup().await;
+  let provider = MemoryProvider::new()
+    .with_file("a.js", "a?.b;\n")
+    .with_file("copy/a.js", "a?.b;\n");
+  let mut stream = jsco_stream(
+    vec!["a.js".to_string(), "copy/a.js".to_string()],
+    Options::default(),
+    Arc::new(provider),
+  )
+  .await
+  .unwrap();
+  let mut reports = Vec::new();
+  while let Some(report) = stream.next().await {
+    reports.push(report);
+  }
+  reports.sort_by(|a, b| a.path.cmp(&b.path));
+
+  assert_eq!(stream.finish().await.count("parse"), 1);
+  assert_eq!(reports[0].path, "a.js");
+  assert_eq!(reports[1].path, "copy/a.js");
+  for report in &reports {
+    assert_eq!(report.found_features.len(), 1, "{}", report.path);
+  }
+}
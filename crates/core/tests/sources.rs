@@ -0,0 +1,197 @@
+mod common;
+
+use std::sync::Arc;
+
+use jsco::{
+  analyze_range, jsco_stream, jsco_with_options, jsco_with_provider, MemoryProvider, Options,
+};
+
+#[tokio::test]
+async fn unreadable_files_come_back_as_failed_reports() {
+  common::setup().await;
+  let options = Options {
+    quiet: true,
+    ..Options::default()
+  };
+  let reports = jsco_with_options(vec!["no-such-dir/typo.js".to_string()], options)
+    .await
+    .unwrap()
+    .reports;
+
+  assert_eq!(reports.len(), 1);
+  assert_eq!(reports[0].path, "no-such-dir/typo.js");
+  let error = reports[0].error.as_deref().expect("read error is recorded");
+  assert!(error.starts_with("couldn't read"), "{}", error);
+  assert!(!reports[0].is_clean());
+}
+
+#[tokio::test]
+async fn memory_provider_serves_virtual_files() {
+  common::setup().await;
+  let provider = MemoryProvider::new()
+    .with_file("src/a.js", "const name = user?.name;\n")
+    .with_file("src/b.js", "const value = left ?? right;\n");
+  let options = Options {
+    quiet: true,
+    ..Options::default()
+  };
+  let mut reports = jsco_with_provider(vec!["src/*.js".to_string()], options, Arc::new(provider))
+    .await
+    .unwrap()
+    .reports;
+  reports.sort_by(|a, b| a.path.cmp(&b.path));
+
+  let keys: Vec<Vec<&str>> = reports
+    .iter()
+    .map(|report| {
+      report
+        .found_features
+        .iter()
+        .map(|feature| feature.feat_type.key())
+        .collect()
+    })
+    .collect();
+  assert_eq!(reports[0].path, "src/a.js");
+  assert_eq!(reports[1].path, "src/b.js");
+  assert_eq!(
+    keys,
+    vec![
+      vec!["javascript.operators.optional_chaining"],
+      vec!["javascript.operators.nullish_coalescing"],
+    ]
+  );
+}
+
+#[tokio::test]
+async fn a_range_keeps_only_the_findings_inside_it() {
+  common::setup().await;
+  let source = "a?.b;\nx ?? y;\nc?.d;\n";
+  let start = source.find("x ??").unwrap();
+  let end = start + "x ?? y;".len();
+  let report = analyze_range("input.js".to_string(), source.to_string(), start, end)
+    .await
+    .unwrap();
+
+  let keys = report
+    .found_features
+    .iter()
+    .map(|feature| feature.feat_type.key())
+    .collect::<Vec<_>>();
+  assert_eq!(keys, ["javascript.operators.nullish_coalescing"]);
+  assert_eq!(report.found_features[0].locations[0].start_line, 2);
+}
+
+#[tokio::test]
+async fn the_stream_yields_a_report_per_input() {
+  common::setup().await;
+  let provider = MemoryProvider::new()
+    .with_file("a.js", "a?.b;\n")
+    .with_file("b.js", "a ?? b;\n")
+    .with_file("c.js", "var c = 1;\n");
+  let mut stream = jsco_stream(
+    vec!["*.js".to_string()],
+    Options::default(),
+    Arc::new(provider),
+  )
+  .await
+  .unwrap();
+  assert_eq!(stream.total, 3);
+
+  let mut paths = Vec::new();
+  while let Some(report) = stream.next().await {
+    paths.push(report.path);
+  }
+  stream.finish().await;
+  paths.sort();
+  assert_eq!(paths, ["a.js", "b.js", "c.js"]);
+}
+
+#[tokio::test]
+async fn globs_pick_up_module_and_jsx_files() {
+  common::setup().await;
+  let dir = std::env::temp_dir().join(format!("jsco-extensions-{}", std::process::id()));
+  std::fs::create_dir_all(dir.join("src")).unwrap();
+  std::fs::write(dir.join("src/index.mjs"), "export const a = b?.c;\n").unwrap();
+  std::fs::write(
+    dir.join("src/view.jsx"),
+    "const view = <div>{a ?? b}</div>;\n",
+  )
+  .unwrap();
+  let options = Options {
+    quiet: true,
+    ..Options::default()
+  };
+
+  let reports = jsco_with_options(
+    vec![format!("{}/src/**/*.mjs", dir.display())],
+    options.clone(),
+  )
+  .await
+  .unwrap()
+  .reports;
+  assert_eq!(reports.len(), 1);
+  assert!(reports[0].path.ends_with("index.mjs"));
+
+  let reports = jsco_with_options(vec![dir.join("src").display().to_string()], options)
+    .await
+    .unwrap()
+    .reports;
+  assert_eq!(reports.len(), 2);
+  assert!(reports.iter().all(|report| !report.has_parse_errors()));
+}
+
+#[tokio::test]
+async fn max_findings_truncates_the_run() {
+  common::setup().await;
+  let provider = Arc::new(
+    MemoryProvider::new()
+      .with_file("a.js", "a?.b;\n")
+      .with_file("b.js", "a ?? b;\n"),
+  );
+  let options = Options {
+    quiet: true,
+    max_findings: Some(1),
+    ..Options::default()
+  };
+  let capped = jsco_with_provider(vec!["*.js".to_string()], options, provider.clone())
+    .await
+    .unwrap();
+  assert!(capped.truncated);
+  assert_eq!(capped.reports.len(), 1);
+
+  let options = Options {
+    quiet: true,
+    ..Options::default()
+  };
+  let full = jsco_with_provider(vec!["*.js".to_string()], options, provider)
+    .await
+    .unwrap();
+  assert!(!full.truncated);
+  assert_eq!(full.reports.len(), 2);
+}
+
+#[tokio::test]
+async fn ignore_patterns_skip_minified_files_in_directory_scans() {
+  common::setup().await;
+  let dir = std::env::temp_dir().join(format!("jsco-ignore-{}", std::process::id()));
+  std::fs::create_dir_all(dir.join("vendor")).unwrap();
+  std::fs::write(dir.join("app.js"), "a?.b;\n").unwrap();
+  std::fs::write(dir.join("vendor/lib.min.js"), "a?.b;\n").unwrap();
+  std::fs::write(dir.join("vendor/lib.js"), "a ?? b;\n").unwrap();
+  let options = Options {
+    quiet: true,
+    ignore_patterns: vec!["**/*.min.js".to_string()],
+    ..Options::default()
+  };
+
+  let reports = jsco_with_options(vec![dir.display().to_string()], options)
+    .await
+    .unwrap()
+    .reports;
+  let mut paths = reports
+    .iter()
+    .map(|report| report.path.rsplit('/').next().unwrap().to_string())
+    .collect::<Vec<_>>();
+  paths.sort();
+  assert_eq!(paths, ["app.js", "lib.js"]);
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use jsco::JscoError;
+
+// No `--bcd-file` here, unlike the other test binaries: it would make the
+// cache moot
+#[test]
+fn stale_cache_is_an_error_offline() {
+  let dir = std::env::temp_dir().join(format!("jsco-stale-cache-{}", std::process::id()));
+  jsco::cache::set_dir(&dir);
+  assert!(jsco::cache::init());
+  std::fs::write(jsco::cache::path("browser-compat-data.json"), "{}").unwrap();
+  std::thread::sleep(Duration::from_millis(10));
+
+  jsco::download::set_offline(true);
+  assert_eq!(
+    jsco::bcd::expire_stale_cache(Duration::ZERO),
+    Err(JscoError::StaleCache)
+  );
+  assert!(jsco::cache::path("browser-compat-data.json").exists());
+
+  jsco::download::set_offline(false);
+  assert_eq!(jsco::bcd::expire_stale_cache(Duration::ZERO), Ok(true));
+  assert!(!jsco::cache::path("browser-compat-data.json").exists());
+}
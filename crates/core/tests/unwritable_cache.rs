@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use jsco::{jsco_with_provider, MemoryProvider, Options};
+
+// Sets its own cache directory, so it can't share a binary with tests that
+// use `common::setup`
+#[tokio::test]
+async fn an_uncreatable_cache_dir_disables_caching() {
+  // A directory can't be created below a regular file
+  let blocker = std::env::temp_dir().join(format!("jsco-cache-blocker-{}", std::process::id()));
+  std::fs::write(&blocker, "").unwrap();
+  jsco::cache::set_dir(blocker.join("cache"));
+  jsco::bcd::use_bcd_file(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bcd.json"),
+  );
+  jsco::download::set_offline(true);
+
+  let provider = MemoryProvider::new().with_file("input.js", "a?.b;\n");
+  let options = Options {
+    quiet: true,
+    ..Options::default()
+  };
+  let reports = jsco_with_provider(vec!["input.js".to_string()], options, Arc::new(provider))
+    .await
+    .unwrap()
+    .reports;
+
+  assert!(!jsco::cache::is_enabled());
+  assert!(!blocker.join("cache").exists());
+  let feature = &reports[0].found_features[0];
+  assert_eq!(
+    feature.feat_type.key(),
+    "javascript.operators.optional_chaining"
+  );
+  assert!(!feature.support.lock().unwrap().is_empty());
+}
@@ -1,7 +1,48 @@
+import { mkdtempSync, writeFileSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+
 import test from 'ava'
 
-import { jsco } from '../index.js'
+import { analyzeFiles, analyzeSource, jsco, run } from '../index.js'
 
 test('jsco', async (t) => {
   t.truthy(await jsco('https://cdn.jsdelivr.net/npm/es-toolkit@1.31.0/dist/browser.global.min.js'))
 })
+
+test('analyzeSource treats its input as code, not a path', async (t) => {
+  const report = await analyzeSource('const name = user?.name\n', 'input.js')
+  t.is(report.path, 'input.js')
+  t.true(report.foundFeatures.some((feature) => feature.featType === 'javascript.operators.optional_chaining'))
+})
+
+test('analyzeFiles reports each file', async (t) => {
+  const dir = mkdtempSync(join(tmpdir(), 'jsco-'))
+  writeFileSync(join(dir, 'a.js'), 'const name = user?.name\n')
+  writeFileSync(join(dir, 'b.js'), 'const value = left ?? right\n')
+  const reports = await analyzeFiles([join(dir, 'a.js'), join(dir, 'b.js')])
+  t.is(reports.length, 2)
+})
+
+test('run exits non-zero for findings at error severity', async (t) => {
+  const file = join(mkdtempSync(join(tmpdir(), 'jsco-')), 'input.js')
+  writeFileSync(file, 'const name = user?.name\n')
+  const args = ['jsco', '-q', '--format', 'keys', file]
+  t.is(await run(args), 0)
+  t.not(await run([...args, '--severity', 'optional_chaining=error']), 0)
+})
+
+test('run fails on incompatible features only with --fail-on-incompatible', async (t) => {
+  const file = join(mkdtempSync(join(tmpdir(), 'jsco-')), 'input.js')
+  writeFileSync(file, 'const name = user?.name\n')
+  const old = ['jsco', '-q', '--format', 'keys', '--browsers', 'chrome 79', file]
+  t.is(await run(old), 0)
+  t.not(await run([...old, '--fail-on-incompatible']), 0)
+  t.is(await run(['jsco', '-q', '--format', 'keys', '--browsers', 'chrome 120', '--fail-on-incompatible', file]), 0)
+})
+
+test('run exits with a distinct code when inputs match no files', async (t) => {
+  const pattern = join(mkdtempSync(join(tmpdir(), 'jsco-')), '*.js')
+  t.is(await run(['jsco', '-q', '--format', 'keys', pattern]), 2)
+  t.is(await run(['jsco', '-q', '--format', 'keys', '--no-files-exit-code', '0', pattern]), 0)
+})
@@ -2,4 +2,4 @@
 
 const { run } = require('./index.js');
 
-run(process.argv.slice(2));
+run(process.argv.slice(2)).then((code) => process.exit(code));
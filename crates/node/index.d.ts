@@ -3,5 +3,57 @@
 
 /* auto-generated by NAPI-RS */
 
-export declare function jsco(sourceCode: string): Promise<any>
-export declare function run(args: Array<string>): Promise<void>
+/** Where a feature or diagnostic occurs in the source. */
+export interface Location {
+  /** Byte offsets into the source. */
+  start: number
+  end: number
+  /** 1-based lines and byte columns. */
+  startLine: number
+  startCol: number
+  endLine: number
+  endCol: number
+  code: string
+  detail?: string
+}
+export interface FeatureReport {
+  /** BCD key of the feature, e.g. `javascript.operators.optional_chaining`. */
+  featType: string
+  locations: Array<Location>
+  /** Browser name -> first supporting version. */
+  support: Record<string, string>
+  mdnUrl: string
+  deprecated: boolean
+  experimental: boolean
+  /** `info`, `warn` or `error`, when the caller assigned one. */
+  severity?: string
+}
+export interface Diagnostic {
+  code: string
+  message: string
+  location: Location
+}
+export interface Report {
+  path: string
+  sourceCode: string
+  /** Minimum browser versions needed by all features in the file. */
+  browserSupport: Record<string, string>
+  /** Browser name -> oldest version that runs the whole file. */
+  requiredVersions: Record<string, string>
+  foundFeatures: Array<FeatureReport>
+  diagnostics: Array<Diagnostic>
+  /** Why the source couldn't be parsed; such a file has no findings. */
+  parseErrors: Array<string>
+  /** Why the source couldn't be read or analyzed at all. */
+  error?: string
+}
+export declare function jsco(sourceCode: string): Promise<Array<Report>>
+/**
+ * Analyzes code passed as a string. `filename` only picks how it's parsed
+ * (script, module, JSX); the code isn't read from disk.
+ */
+export declare function analyzeSource(code: string, filename: string): Promise<Report>
+/** Analyzes files, directories, globs or URLs, one report per source. */
+export declare function analyzeFiles(paths: Array<string>): Promise<Array<Report>>
+/** Runs the CLI and resolves to its exit code. */
+export declare function run(args: Array<string>): Promise<number>
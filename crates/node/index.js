@@ -310,7 +310,9 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { jsco, run } = nativeBinding
+const { jsco, analyzeSource, analyzeFiles, run } = nativeBinding
 
 module.exports.jsco = jsco
+module.exports.analyzeSource = analyzeSource
+module.exports.analyzeFiles = analyzeFiles
 module.exports.run = run
@@ -6,13 +6,39 @@ use napi::bindgen_prelude::*;
 #[macro_use]
 extern crate napi_derive;
 
+mod report;
+
+use report::Report;
+
+#[napi]
+pub async fn jsco(source_code: String) -> Result<Vec<Report>> {
+  let report = core::jsco(vec![source_code.into()])
+    .await
+    .map_err(|err| Error::from_reason(err.to_string()))?;
+  Ok(report.iter().map(Report::from).collect())
+}
+
+/// Analyzes code passed as a string. `filename` only picks how it's parsed
+/// (script, module, JSX); the code isn't read from disk.
+#[napi]
+pub async fn analyze_source(code: String, filename: String) -> Result<Report> {
+  let report = core::analyze_source(&filename, &code)
+    .await
+    .map_err(|err| Error::from_reason(err.to_string()))?;
+  Ok(Report::from(&report))
+}
+
+/// Analyzes files, directories, globs or URLs, one report per source.
 #[napi]
-pub async fn jsco(source_code: String) -> Result<serde_json::Value> {
-  let report = core::jsco(vec![source_code.into()]).await;
-  Ok(serde_json::to_value(report).unwrap())
+pub async fn analyze_files(paths: Vec<String>) -> Result<Vec<Report>> {
+  let reports = core::jsco(paths)
+    .await
+    .map_err(|err| Error::from_reason(err.to_string()))?;
+  Ok(reports.iter().map(Report::from).collect())
 }
 
+/// Runs the CLI and resolves to its exit code.
 #[napi]
-pub async fn run(args: Vec<String>) -> () {
-  jsco_cli::run(args).await;
+pub async fn run(args: Vec<String>) -> i32 {
+  jsco_cli::run(args).await
 }
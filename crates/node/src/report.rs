@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use jsco as core;
+
+/// Where a feature or diagnostic occurs in the source.
+#[napi(object)]
+pub struct Location {
+  /// Byte offsets into the source.
+  pub start: u32,
+  pub end: u32,
+  /// 1-based lines and byte columns.
+  pub start_line: u32,
+  pub start_col: u32,
+  pub end_line: u32,
+  pub end_col: u32,
+  pub code: String,
+  pub detail: Option<String>,
+}
+
+impl From<&core::feature::Location> for Location {
+  fn from(location: &core::feature::Location) -> Self {
+    Self {
+      start: location.start as u32,
+      end: location.end as u32,
+      start_line: location.start_line as u32,
+      start_col: location.start_col as u32,
+      end_line: location.end_line as u32,
+      end_col: location.end_col as u32,
+      code: location.code.clone(),
+      detail: location.detail.clone(),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct FeatureReport {
+  /// BCD key of the feature, e.g. `javascript.operators.optional_chaining`.
+  pub feat_type: String,
+  pub locations: Vec<Location>,
+  /// Browser name -> first supporting version.
+  pub support: HashMap<String, String>,
+  pub mdn_url: String,
+  pub deprecated: bool,
+  pub experimental: bool,
+  /// `info`, `warn` or `error`, when the caller assigned one.
+  pub severity: Option<String>,
+}
+
+impl From<&core::feature::FeatureReport> for FeatureReport {
+  fn from(feature: &core::feature::FeatureReport) -> Self {
+    Self {
+      feat_type: feature.feat_type.key().to_string(),
+      locations: feature.locations.iter().map(Location::from).collect(),
+      support: feature.support.lock().unwrap().clone(),
+      mdn_url: feature.mdn_url.clone(),
+      deprecated: feature.deprecated,
+      experimental: feature.experimental,
+      severity: feature
+        .severity
+        .map(|severity| format!("{:?}", severity).to_lowercase()),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct Diagnostic {
+  pub code: String,
+  pub message: String,
+  pub location: Location,
+}
+
+impl From<&core::diagnostic::Diagnostic> for Diagnostic {
+  fn from(diagnostic: &core::diagnostic::Diagnostic) -> Self {
+    Self {
+      code: diagnostic.code.clone(),
+      message: diagnostic.message.clone(),
+      location: Location::from(&diagnostic.location),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct Report {
+  pub path: String,
+  pub source_code: String,
+  /// Minimum browser versions needed by all features in the file.
+  pub browser_support: HashMap<String, String>,
+  /// Browser name -> oldest version that runs the whole file.
+  pub required_versions: HashMap<String, String>,
+  pub found_features: Vec<FeatureReport>,
+  pub diagnostics: Vec<Diagnostic>,
+  /// Why the source couldn't be parsed; such a file has no findings.
+  pub parse_errors: Vec<String>,
+  /// Why the source couldn't be read or analyzed at all.
+  pub error: Option<String>,
+}
+
+impl From<&core::report::Report> for Report {
+  fn from(report: &core::report::Report) -> Self {
+    Self {
+      path: report.path.clone(),
+      source_code: report.source_code.clone(),
+      browser_support: report.browser_support.lock().unwrap().clone(),
+      required_versions: report.required_versions.clone(),
+      found_features: report
+        .found_features
+        .iter()
+        .map(FeatureReport::from)
+        .collect(),
+      diagnostics: report
+        .diagnostics
+        .lock()
+        .unwrap()
+        .iter()
+        .map(Diagnostic::from)
+        .collect(),
+      parse_errors: report.parse_errors.lock().unwrap().clone(),
+      error: report.error.clone(),
+    }
+  }
+}